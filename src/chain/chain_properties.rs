@@ -7,6 +7,12 @@ pub struct ChainProperties {
     pub rpc_urls: Vec<Url>,
     pub rpc_headers: HeaderMap,
     pub name: String,
+    /// Chain id used for EIP-1191 address checksums, if the chain requires them
+    pub eip1191_chain_id: Option<u64>,
+    /// Addresses of the NonfungiblePositionManager contracts holding liquidity positions
+    pub position_managers: Vec<String>,
+    /// Addresses of the Aave V3 compatible lending pools
+    pub lending_pools: Vec<String>,
     pub native_token: Token,
     pub stables: Vec<Token>,
 }
@@ -1,20 +1,36 @@
+mod aave_v3;
+mod nft;
+mod transfers;
+mod uniswap_v3;
+
 use sha3::{Digest, Keccak256};
 
 use num_bigint::BigUint;
 use num_traits::ToPrimitive;
-use serde::Deserialize;
+use serde::{de::DeserializeOwned, Deserialize};
 use serde_json::{json, Value};
 
-use crate::{chain::*, utils::retry::get_retry_time};
+use crate::{
+    chain::*,
+    utils::{retry::get_retry_time, support_option::ToSupported},
+};
 
 pub struct EvmChain {
     properties: ChainProperties,
     http_client: Client,
 }
 
+/// Reason a contract read did not return a value
+enum CallError {
+    /// The request did not go through, so it is worth retrying
+    Failed,
+    /// The call reverted or returned data that can not be decoded
+    Reverted,
+}
+
 #[derive(Deserialize, Debug)]
-struct EthCallResponse {
-    result: String,
+struct RpcResponse<T> {
+    result: T,
 }
 
 impl From<&Chain> for EvmChain {
@@ -27,12 +43,262 @@ impl From<&Chain> for EvmChain {
 }
 
 impl EvmChain {
+    /// Checksums a 40 hex character address following EIP-55, or EIP-1191 when the
+    /// chain id is given
+    fn checksum_address(hex_address: &str, eip1191_chain_id: Option<u64>) -> String {
+        let hex_address = hex_address.to_lowercase();
+        let mut hasher = Keccak256::new();
+        match eip1191_chain_id {
+            Some(chain_id) => hasher.update(format!("{chain_id}0x{hex_address}")),
+            None => hasher.update(&hex_address),
+        }
+        let hash = hasher.finalize();
+        let mut checksummed_address = String::from("0x");
+        for (i, c) in hex_address.chars().enumerate() {
+            if (hash[i / 2] >> (4 - (i % 2) * 4) & 0xf) > 7 {
+                checksummed_address.push(c.to_ascii_uppercase());
+            } else {
+                checksummed_address.push(c);
+            }
+        }
+        checksummed_address
+    }
+    /// Left pads an address into a 32 byte ABI word
+    fn encode_address(address: &str) -> String {
+        format!("{:0>64}", address.trim_start_matches("0x").to_lowercase())
+    }
+    fn encode_uint(value: &BigUint) -> String {
+        format!("{:0>64}", value.to_str_radix(16))
+    }
+    /// Splits ABI encoded return data into its 32 byte words
+    fn decode_words(data: &str) -> Option<Vec<BigUint>> {
+        let data = data.strip_prefix("0x")?;
+        if data.is_empty() || data.len() % 64 != 0 {
+            return None;
+        }
+        (0..data.len() / 64)
+            .map(|i| BigUint::parse_bytes(&data.as_bytes()[i * 64..(i + 1) * 64], 16))
+            .collect()
+    }
+    fn decode_address(word: &BigUint) -> String {
+        Self::checksum_address(&format!("{:0>40}", word.to_str_radix(16)), None)
+    }
+    /// Decodes a returned `string`, or a `bytes32` for tokens predating the standard
+    fn decode_string(data: &str) -> Option<String> {
+        let bytes = data
+            .strip_prefix("0x")?
+            .as_bytes()
+            .chunks(2)
+            .map(|b| u8::from_str_radix(std::str::from_utf8(b).ok()?, 16).ok())
+            .collect::<Option<Vec<_>>>()?;
+        let string_bytes = if bytes.len() == 32 {
+            bytes.into_iter().take_while(|b| *b != 0).collect()
+        } else {
+            let offset = BigUint::from_bytes_be(bytes.get(..32)?).to_usize()?;
+            let len = BigUint::from_bytes_be(bytes.get(offset..offset + 32)?).to_usize()?;
+            bytes.get(offset + 32..offset + 32 + len)?.to_vec()
+        };
+        String::from_utf8(string_bytes)
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+    }
+    async fn eth_call(
+        &self,
+        to: &str,
+        data: String,
+        rpc_index: usize,
+    ) -> (Option<String>, Option<f32>) {
+        let params = json!([
+            {
+                "to": to,
+                "data": data,
+            },
+            "latest"
+        ]);
+        self.rpc_call("eth_call", params, rpc_index).await
+    }
+    /// Like `eth_call`, but tells a call that reverted (`Some(None)`) apart from a request
+    /// that failed (`None`), which is worth retrying
+    async fn eth_call_checked(
+        &self,
+        to: &str,
+        data: String,
+        rpc_index: usize,
+    ) -> (Option<Option<String>>, Option<f32>) {
+        let params = json!([
+            {
+                "to": to,
+                "data": data,
+            },
+            "latest"
+        ]);
+        let (response, wait_time) = self.rpc_request("eth_call", params, rpc_index).await;
+        let result = response.and_then(|x| match (x.get("result"), x.get("error")) {
+            (Some(Value::String(result)), _) => Some(Some(result.clone())),
+            (_, Some(_)) => Some(None),
+            _ => None,
+        });
+        (result, wait_time)
+    }
+    async fn eth_call_words(
+        &self,
+        to: &str,
+        data: String,
+        rpc_index: usize,
+    ) -> Option<Vec<BigUint>> {
+        Self::decode_words(&self.eth_call(to, data, rpc_index).await.0?)
+    }
+    /// Like `eth_call_words`, telling why no words were returned
+    async fn eth_call_words_checked(
+        &self,
+        to: &str,
+        data: String,
+        rpc_index: usize,
+    ) -> Result<Vec<BigUint>, CallError> {
+        match self.eth_call_checked(to, data, rpc_index).await.0 {
+            Some(Some(x)) => Self::decode_words(&x).ok_or(CallError::Reverted),
+            Some(None) => Err(CallError::Reverted),
+            None => Err(CallError::Failed),
+        }
+    }
+    /// Like `fetch_token`, telling a token whose metadata reverts apart from a failed request
+    async fn fetch_token_checked(
+        &self,
+        token_address: &str,
+        rpc_index: usize,
+    ) -> Result<Token, CallError> {
+        let decimals = self
+            .eth_call_words_checked(token_address, "0x313ce567".to_string(), rpc_index)
+            .await?
+            .first()
+            .and_then(|x| x.to_usize())
+            .ok_or(CallError::Reverted)?;
+        let symbol = match dexscreener::pairs::get_token_symbol(token_address).await {
+            Some(x) => x,
+            None => match self
+                .eth_call_checked(token_address, "0x95d89b41".to_string(), rpc_index)
+                .await
+                .0
+            {
+                Some(x) => x
+                    .as_deref()
+                    .and_then(Self::decode_string)
+                    .ok_or(CallError::Reverted)?,
+                None => return Err(CallError::Failed),
+            },
+        };
+        Ok(Token::hardcode(&symbol, token_address, decimals))
+    }
+    async fn fetch_token(&self, token_address: &str, rpc_index: usize) -> Option<Token> {
+        let decimals = self.get_token_decimals(token_address, rpc_index).await?;
+        let symbol = self.get_token_symbol(token_address, rpc_index).await?;
+        Some(Token::hardcode(&symbol, token_address, decimals))
+    }
+    /// ERC-4626 vaults expose their underlying asset through `asset()` and convert
+    /// shares with `convertToAssets(uint256)`
+    async fn get_vault_asset(&self, token_address: &str, rpc_index: usize) -> Option<String> {
+        let asset_words = self
+            .eth_call_words(token_address, "0x38d52e0f".to_string(), rpc_index)
+            .await?;
+        let asset_address = match asset_words.as_slice() {
+            [word] if *word != BigUint::ZERO => Self::decode_address(word),
+            _ => return None,
+        };
+        let convert_data = format!("0x07a2d13a{}", Self::encode_uint(&BigUint::from(1u8)));
+        self.eth_call_words(token_address, convert_data, rpc_index)
+            .await?;
+        Some(asset_address)
+    }
+    /// Uniswap V2 compatible pairs expose `token0()`, `token1()`, `getReserves()` and
+    /// `totalSupply()`
+    async fn get_pair_tokens(
+        &self,
+        token_address: &str,
+        rpc_index: usize,
+    ) -> Option<(String, String)> {
+        let token0_words = self
+            .eth_call_words(token_address, "0x0dfe1681".to_string(), rpc_index)
+            .await?;
+        let token1_words = self
+            .eth_call_words(token_address, "0xd21220a7".to_string(), rpc_index)
+            .await?;
+        let (token0_address, token1_address) =
+            match (token0_words.as_slice(), token1_words.as_slice()) {
+                ([word0], [word1]) if *word0 != BigUint::ZERO && *word1 != BigUint::ZERO => {
+                    (Self::decode_address(word0), Self::decode_address(word1))
+                }
+                _ => return None,
+            };
+        self.get_pair_reserves(token_address, rpc_index).await.0??;
+        Some((token0_address, token1_address))
+    }
+    /// Returns the pair reserves of both tokens and the LP token total supply, or
+    /// `Some(None)` when the pair can not be read
+    async fn get_pair_reserves(
+        &self,
+        pair_address: &str,
+        rpc_index: usize,
+    ) -> (Option<Option<(BigUint, BigUint, BigUint)>>, Option<f32>) {
+        let (reserves_hex, wait_time) = self
+            .eth_call_checked(pair_address, "0x0902f1ac".to_string(), rpc_index)
+            .await;
+        let reserves = match reserves_hex {
+            Some(x) => x.as_deref().and_then(Self::decode_words),
+            None => return (None, wait_time),
+        };
+        let reserves = match reserves {
+            Some(x) if x.len() == 3 => x,
+            _ => return (Some(None), wait_time),
+        };
+        let (supply_hex, wait_time) = self
+            .eth_call_checked(pair_address, "0x18160ddd".to_string(), rpc_index)
+            .await;
+        let total_supply = match supply_hex {
+            Some(x) => x.as_deref().and_then(Self::decode_words),
+            None => return (None, wait_time),
+        };
+        let total_supply = match total_supply {
+            Some(x) if x.len() == 1 => x[0].clone(),
+            _ => return (Some(None), wait_time),
+        };
+        (
+            Some(Some((
+                reserves[0].clone(),
+                reserves[1].clone(),
+                total_supply,
+            ))),
+            wait_time,
+        )
+    }
     async fn rpc_call(
         &self,
         method: &str,
         params: Value,
         rpc_index: usize,
     ) -> (Option<String>, Option<f32>) {
+        self.rpc_call_json(method, params, rpc_index).await
+    }
+    async fn rpc_call_json<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Value,
+        rpc_index: usize,
+    ) -> (Option<T>, Option<f32>) {
+        let (response, seconds) = self.rpc_request(method, params, rpc_index).await;
+        (
+            response
+                .and_then(|x| serde_json::from_value::<RpcResponse<T>>(x).ok())
+                .map(|x| x.result),
+            seconds,
+        )
+    }
+    /// Sends a JSON-RPC request, returning the whole response body
+    async fn rpc_request(
+        &self,
+        method: &str,
+        params: Value,
+        rpc_index: usize,
+    ) -> (Option<Value>, Option<f32>) {
         let payload = json!({
             "jsonrpc": "2.0",
             "id": "1",
@@ -51,14 +317,7 @@ impl EvmChain {
             None => return (None, None),
         };
         let seconds = get_retry_time(&response);
-        (
-            response
-                .json::<EthCallResponse>()
-                .await
-                .ok()
-                .map(|x| x.result),
-            seconds,
-        )
+        (response.json::<Value>().await.ok(), seconds)
     }
 }
 
@@ -85,7 +344,7 @@ impl ChainOps for EvmChain {
         let params = json!([
             {
                 "to": token.address,
-                "data": format!("0x70a08231000000000000000000000000{}", &address[2..])
+                "data": format!("0x70a08231{}", Self::encode_address(address))
             },
             "latest"
         ]);
@@ -113,6 +372,94 @@ impl ChainOps for EvmChain {
         let decimals_hex = self.rpc_call("eth_call", params, rpc_index).await.0?;
         BigUint::parse_bytes(&decimals_hex.as_bytes()[2..], 16)?.to_usize()
     }
+    async fn get_token_symbol(&self, token_address: &str, rpc_index: usize) -> Option<String> {
+        if let Some(symbol) = dexscreener::pairs::get_token_symbol(token_address).await {
+            return Some(symbol);
+        }
+        // Tokens such as vault shares are often not traded, so fallback to the contract
+        let symbol_data = self
+            .eth_call(token_address, "0x95d89b41".to_string(), rpc_index)
+            .await
+            .0?;
+        Self::decode_string(&symbol_data)
+    }
+    async fn get_token_kind(
+        &self,
+        token_address: &str,
+        rpc_index: usize,
+    ) -> SupportOption<TokenKind> {
+        if let Some(asset_address) = self.get_vault_asset(token_address, rpc_index).await {
+            let asset = self
+                .fetch_token(&asset_address, rpc_index)
+                .await
+                .to_supported()?;
+            return SupportOption::SupportedSome(TokenKind::Vault {
+                asset: Box::new(asset),
+            });
+        }
+        if let Some((token0_address, token1_address)) =
+            self.get_pair_tokens(token_address, rpc_index).await
+        {
+            let token0 = self
+                .fetch_token(&token0_address, rpc_index)
+                .await
+                .to_supported()?;
+            let token1 = self
+                .fetch_token(&token1_address, rpc_index)
+                .await
+                .to_supported()?;
+            return SupportOption::SupportedSome(TokenKind::LiquidityPair {
+                token0: Box::new(token0),
+                token1: Box::new(token1),
+            });
+        }
+        SupportOption::SupportedSome(TokenKind::Standard)
+    }
+    async fn get_underlying_balances(
+        &self,
+        token: &Token,
+        balance: &BigUint,
+        rpc_index: usize,
+    ) -> (Option<Vec<(Token, BigUint)>>, Option<f32>) {
+        match &token.kind {
+            TokenKind::Standard => (Some(Vec::new()), None),
+            TokenKind::Vault { asset } => {
+                let data = format!("0x07a2d13a{}", Self::encode_uint(balance));
+                let (assets_hex, wait_time) =
+                    self.eth_call_checked(&token.address, data, rpc_index).await;
+                (
+                    assets_hex.map(|assets_hex| {
+                        assets_hex
+                            .as_deref()
+                            .and_then(Self::decode_words)
+                            .and_then(|words| words.first().cloned())
+                            .map(|assets| Vec::from([(*asset.clone(), assets)]))
+                            // Valued as the share token itself when the vault can not convert
+                            .unwrap_or_default()
+                    }),
+                    wait_time,
+                )
+            }
+            TokenKind::LiquidityPair { token0, token1 } => {
+                let (reserves, wait_time) = self.get_pair_reserves(&token.address, rpc_index).await;
+                (
+                    reserves.map(|reserves| match reserves {
+                        Some((reserve0, reserve1, total_supply))
+                            if total_supply != BigUint::ZERO =>
+                        {
+                            Vec::from([
+                                (*token0.clone(), reserve0 * balance / &total_supply),
+                                (*token1.clone(), reserve1 * balance / &total_supply),
+                            ])
+                        }
+                        // Valued as the LP token itself when the pair can not be read
+                        _ => Vec::new(),
+                    }),
+                    wait_time,
+                )
+            }
+        }
+    }
     async fn scan_for_tokens(
         &self,
         _address: &str,
@@ -120,28 +467,162 @@ impl ChainOps for EvmChain {
     ) -> SupportOption<Vec<Token>> {
         SupportOption::Unsupported
     }
+    async fn get_liquidity_positions(
+        &self,
+        address: &str,
+        rpc_index: usize,
+    ) -> SupportOption<Vec<LiquidityPosition>> {
+        let mut positions = Vec::new();
+        for manager in &self.properties.position_managers {
+            positions.extend(
+                self.get_uniswap_v3_positions(manager, address, rpc_index)
+                    .await
+                    .to_supported()?,
+            );
+        }
+        SupportOption::SupportedSome(positions)
+    }
+    async fn get_lending_positions(
+        &self,
+        address: &str,
+        rpc_index: usize,
+    ) -> SupportOption<Vec<LendingPosition>> {
+        let mut positions = Vec::new();
+        for pool in &self.properties.lending_pools {
+            positions.extend(
+                self.get_aave_v3_position(pool, address, rpc_index)
+                    .await
+                    .to_supported()?,
+            );
+        }
+        SupportOption::SupportedSome(positions)
+    }
+    async fn get_nft_collection(
+        &self,
+        address: &str,
+        token_ids: Vec<String>,
+        rpc_index: usize,
+    ) -> SupportOption<NftCollection> {
+        self.get_erc_nft_collection(address, token_ids, rpc_index)
+            .await
+            .into()
+    }
+    async fn get_nft_count(
+        &self,
+        collection: &NftCollection,
+        address: &str,
+        rpc_index: usize,
+    ) -> (Option<BigUint>, Option<f32>) {
+        self.get_erc_nft_count(collection, address, rpc_index).await
+    }
+    async fn get_transfers(
+        &self,
+        address: &str,
+        tokens: &[Token],
+        rpc_index: usize,
+    ) -> SupportOption<TransferHistory> {
+        self.get_erc20_transfers(address, tokens, rpc_index)
+            .await
+            .map(TransferHistory::from)
+            .into()
+    }
     fn parse_wallet_address(&self, address: &str) -> Option<String> {
-        if !address.starts_with("0x") {
-            return None;
+        self.validate_wallet_address(address).ok()
+    }
+    fn validate_wallet_address(&self, address: &str) -> Result<String, String> {
+        let hex_address = match address.strip_prefix("0x") {
+            Some(x) => x,
+            None => return Err("missing 0x prefix".to_string()),
+        };
+        if hex_address.len() != 40 {
+            return Err(format!(
+                "expected 40 hex characters after 0x, got {}",
+                hex_address.len()
+            ));
         }
-        let address = &address[2..];
-        if address.len() != 40 {
-            return None;
+        if !hex_address.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err("contains non-hex characters".to_string());
         }
-        if !address.chars().all(|c| c.is_ascii_hexdigit()) {
-            return None;
+        let checksummed_address =
+            Self::checksum_address(hex_address, self.properties.eip1191_chain_id);
+        let is_mixed_case = hex_address.chars().any(|c| c.is_ascii_lowercase())
+            && hex_address.chars().any(|c| c.is_ascii_uppercase());
+        if is_mixed_case && checksummed_address[2..] != *hex_address {
+            return Err(match self.properties.eip1191_chain_id {
+                Some(chain_id) => {
+                    format!("mixed-case checksum does not match (EIP-1191, chain id {chain_id})")
+                }
+                None => "mixed-case checksum does not match (EIP-55)".to_string(),
+            });
         }
-        let mut hasher = Keccak256::new();
-        hasher.update(address.to_lowercase());
-        let hash = hasher.finalize();
-        let mut checksummed_address = String::from("0x");
-        for (i, c) in address.chars().enumerate() {
-            if (hash[i / 2] >> (4 - (i % 2) * 4) & 0xf) > 7 {
-                checksummed_address.push_str(&c.to_uppercase().to_string());
-            } else {
-                checksummed_address.push(c);
-            }
+        Ok(checksummed_address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EvmChain;
+    use crate::chain::{chain_type::ChainType, Chain, ChainOps};
+
+    fn checksum(address: &str, eip1191_chain_id: Option<u64>) -> String {
+        EvmChain::checksum_address(address.trim_start_matches("0x"), eip1191_chain_id)
+    }
+
+    #[test]
+    fn checksum_address_follows_eip55() {
+        for address in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            assert_eq!(checksum(&address.to_lowercase(), None), address);
+            assert_eq!(checksum(&address[2..].to_uppercase(), None), address);
+        }
+    }
+
+    #[test]
+    fn checksum_address_follows_eip1191() {
+        for (chain_id, address) in [
+            (30, "0x5aaEB6053f3e94c9b9a09f33669435E7ef1bEAeD"),
+            (30, "0xFb6916095cA1Df60bb79ce92cE3EA74c37c5d359"),
+            (30, "0xDBF03B407c01E7CD3cBea99509D93F8Dddc8C6FB"),
+            (30, "0xD1220A0Cf47c7B9BE7a2e6ba89F429762E7B9adB"),
+            (31, "0x5aAeb6053F3e94c9b9A09F33669435E7EF1BEaEd"),
+            (31, "0xFb6916095CA1dF60bb79CE92ce3Ea74C37c5D359"),
+            (31, "0xdbF03B407C01E7cd3cbEa99509D93f8dDDc8C6fB"),
+            (31, "0xd1220a0CF47c7B9Be7A2E6Ba89f429762E7b9adB"),
+        ] {
+            assert_eq!(checksum(&address.to_lowercase(), Some(chain_id)), address);
         }
-        Some(checksummed_address)
+    }
+
+    #[test]
+    fn validate_wallet_address_rejects_bad_checksums() {
+        let mut chain = Chain::new(
+            ChainType::Evm,
+            Vec::from(["https://example.com"]),
+            "Test",
+            "ETH",
+            "0x0000000000000000000000000000000000000000",
+            18,
+            Vec::new(),
+        );
+        let address = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+        let evm_chain = EvmChain::from(&chain);
+        assert!(evm_chain.validate_wallet_address(address).is_ok());
+        assert!(evm_chain
+            .validate_wallet_address(&address.to_lowercase())
+            .is_ok());
+        assert!(evm_chain
+            .validate_wallet_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD")
+            .is_err());
+
+        chain.properties.eip1191_chain_id = Some(30);
+        let evm_chain = EvmChain::from(&chain);
+        assert!(evm_chain.validate_wallet_address(address).is_err());
+        assert!(evm_chain
+            .validate_wallet_address("0x5aaEB6053f3e94c9b9a09f33669435E7ef1bEAeD")
+            .is_ok());
     }
 }
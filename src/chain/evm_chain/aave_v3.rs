@@ -0,0 +1,148 @@
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+
+use crate::chain::{lending::LendingPosition, token::Token};
+
+use super::{CallError, EvmChain};
+
+/// Token addresses tied to a reserve, as returned by `getReserveData(address)`
+struct ReserveTokens {
+    a_token: String,
+    variable_debt_token: String,
+}
+
+impl EvmChain {
+    async fn get_reserves_list(
+        &self,
+        pool: &str,
+        rpc_index: usize,
+    ) -> Result<Vec<String>, CallError> {
+        let words = self
+            .eth_call_words_checked(pool, "0xd1946dbc".to_string(), rpc_index)
+            .await?;
+        // Dynamic array: offset, length and then the items
+        let len = words
+            .get(1)
+            .and_then(|x| x.to_usize())
+            .ok_or(CallError::Reverted)?;
+        Ok(words
+            .get(2..2 + len)
+            .ok_or(CallError::Reverted)?
+            .iter()
+            .map(Self::decode_address)
+            .collect())
+    }
+    async fn get_reserve_tokens(
+        &self,
+        pool: &str,
+        asset: &str,
+        rpc_index: usize,
+    ) -> Result<ReserveTokens, CallError> {
+        let data = format!("0x35ea6a75{}", Self::encode_address(asset));
+        let words = self.eth_call_words_checked(pool, data, rpc_index).await?;
+        match (words.get(8), words.get(10)) {
+            (Some(a_token), Some(variable_debt_token)) => Ok(ReserveTokens {
+                a_token: Self::decode_address(a_token),
+                variable_debt_token: Self::decode_address(variable_debt_token),
+            }),
+            _ => Err(CallError::Reverted),
+        }
+    }
+    async fn get_balance_of(
+        &self,
+        token: &str,
+        address: &str,
+        rpc_index: usize,
+    ) -> Result<BigUint, CallError> {
+        let data = format!("0x70a08231{}", Self::encode_address(address));
+        self.eth_call_words_checked(token, data, rpc_index)
+            .await?
+            .first()
+            .cloned()
+            .ok_or(CallError::Reverted)
+    }
+    /// Reads the collateral and debt of the account in a single reserve, as the aToken
+    /// address and the token amounts
+    async fn get_reserve_position(
+        &self,
+        pool: &str,
+        asset: &str,
+        address: &str,
+        rpc_index: usize,
+    ) -> Result<Option<(String, Token, BigUint, BigUint)>, CallError> {
+        let reserve = self.get_reserve_tokens(pool, asset, rpc_index).await?;
+        let supplied = self
+            .get_balance_of(&reserve.a_token, address, rpc_index)
+            .await?;
+        let borrowed = self
+            .get_balance_of(&reserve.variable_debt_token, address, rpc_index)
+            .await?;
+        if supplied == BigUint::ZERO && borrowed == BigUint::ZERO {
+            return Ok(None);
+        }
+        let token = self.fetch_token_checked(asset, rpc_index).await?;
+        Ok(Some((reserve.a_token, token, supplied, borrowed)))
+    }
+    async fn get_pool_position(
+        &self,
+        pool: &str,
+        address: &str,
+        rpc_index: usize,
+    ) -> Result<Option<LendingPosition>, CallError> {
+        let data = format!("0xbf92857c{}", Self::encode_address(address));
+        let account_data = self.eth_call_words_checked(pool, data, rpc_index).await?;
+        let (total_collateral, total_debt) = match account_data.as_slice() {
+            [total_collateral, total_debt, ..] => (total_collateral, total_debt),
+            _ => return Err(CallError::Reverted),
+        };
+        if *total_collateral == BigUint::ZERO && *total_debt == BigUint::ZERO {
+            return Ok(None);
+        }
+        let health_factor = (*total_debt != BigUint::ZERO)
+            .then(|| account_data.get(5)?.to_f64().map(|x| x / 1e18))
+            .flatten();
+        let mut collateral = Vec::new();
+        let mut debt = Vec::new();
+        let mut collateral_tokens = Vec::new();
+        for asset in self.get_reserves_list(pool, rpc_index).await? {
+            let (a_token, token, supplied, borrowed) = match self
+                .get_reserve_position(pool, &asset, address, rpc_index)
+                .await
+            {
+                Ok(Some(x)) => x,
+                Ok(None) => continue,
+                // Reserves that can not be read are left out instead of failing the whole
+                // position
+                Err(CallError::Reverted) => continue,
+                Err(CallError::Failed) => return Err(CallError::Failed),
+            };
+            if supplied != BigUint::ZERO {
+                collateral.push((token.clone(), supplied));
+                collateral_tokens.push(a_token);
+            }
+            if borrowed != BigUint::ZERO {
+                debt.push((token, borrowed));
+            }
+        }
+        Ok(Some(LendingPosition {
+            collateral,
+            debt,
+            collateral_tokens,
+            health_factor,
+        }))
+    }
+    /// Reads the account collateral and debt in an Aave V3 pool, returning none if the
+    /// account never used it
+    pub(super) async fn get_aave_v3_position(
+        &self,
+        pool: &str,
+        address: &str,
+        rpc_index: usize,
+    ) -> Option<Option<LendingPosition>> {
+        match self.get_pool_position(pool, address, rpc_index).await {
+            Ok(x) => Some(x),
+            Err(CallError::Reverted) => Some(None),
+            Err(CallError::Failed) => None,
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use std::str::FromStr;
+
+use num_bigint::BigUint;
+
+use crate::chain::nft::{NftCollection, NftStandard};
+
+use super::EvmChain;
+
+static ERC721_INTERFACE_ID: &str = "80ac58cd";
+static ERC1155_INTERFACE_ID: &str = "d9b67a26";
+
+impl EvmChain {
+    /// Checks ERC-165 `supportsInterface(bytes4)`
+    async fn supports_interface(
+        &self,
+        contract: &str,
+        interface_id: &str,
+        rpc_index: usize,
+    ) -> bool {
+        let data = format!("0x01ffc9a7{interface_id:0<64}");
+        self.eth_call_words(contract, data, rpc_index)
+            .await
+            .is_some_and(|words| words.first().is_some_and(|w| *w == BigUint::from(1u8)))
+    }
+    pub(super) async fn get_erc_nft_collection(
+        &self,
+        contract: &str,
+        token_ids: Vec<String>,
+        rpc_index: usize,
+    ) -> Option<NftCollection> {
+        let standard = if self
+            .supports_interface(contract, ERC721_INTERFACE_ID, rpc_index)
+            .await
+        {
+            NftStandard::Erc721
+        } else if self
+            .supports_interface(contract, ERC1155_INTERFACE_ID, rpc_index)
+            .await
+        {
+            NftStandard::Erc1155
+        } else {
+            return None;
+        };
+        // name() followed by symbol(), as both are optional
+        let mut name = None;
+        for selector in ["0x06fdde03", "0x95d89b41"] {
+            let data = self
+                .eth_call(contract, selector.to_string(), rpc_index)
+                .await
+                .0;
+            name = data.as_deref().and_then(Self::decode_string);
+            if name.is_some() {
+                break;
+            }
+        }
+        Some(NftCollection {
+            name: name.unwrap_or("Unknown".to_string()),
+            address: contract.to_string(),
+            standard,
+            token_ids,
+            floor_usd: None,
+        })
+    }
+    /// Counts the tokens of the collection owned by the account, using `balanceOf` or
+    /// `ownerOf` for ERC-721 and `balanceOfBatch` for ERC-1155
+    pub(super) async fn get_erc_nft_count(
+        &self,
+        collection: &NftCollection,
+        address: &str,
+        rpc_index: usize,
+    ) -> (Option<BigUint>, Option<f32>) {
+        let token_ids = match collection
+            .token_ids
+            .iter()
+            .map(|id| BigUint::from_str(id).ok())
+            .collect::<Option<Vec<_>>>()
+        {
+            Some(x) => x,
+            None => return (None, None),
+        };
+        match collection.standard {
+            NftStandard::Erc721 if token_ids.is_empty() => {
+                let data = format!("0x70a08231{}", Self::encode_address(address));
+                let (count_hex, wait_time) = self
+                    .eth_call_checked(&collection.address, data, rpc_index)
+                    .await;
+                (
+                    count_hex.map(|x| {
+                        x.as_deref()
+                            .and_then(Self::decode_words)
+                            .and_then(|words| words.first().cloned())
+                            // Contracts not following the standard hold none
+                            .unwrap_or_default()
+                    }),
+                    wait_time,
+                )
+            }
+            NftStandard::Erc721 => {
+                let owner = Self::encode_address(address);
+                let mut count = BigUint::ZERO;
+                for id in &token_ids {
+                    let data = format!("0x6352211e{}", Self::encode_uint(id));
+                    let (owner_hex, wait_time) = self
+                        .eth_call_checked(&collection.address, data, rpc_index)
+                        .await;
+                    let owner_hex = match owner_hex {
+                        Some(x) => x,
+                        None => return (None, wait_time),
+                    };
+                    // Burned or nonexistent tokens make the call revert, so anything other
+                    // than a matching owner is not counted
+                    if owner_hex.is_some_and(|x| x.len() == 66 && x[2..].to_lowercase() == owner) {
+                        count += 1u8;
+                    }
+                }
+                (Some(count), None)
+            }
+            NftStandard::Erc1155 => {
+                let n = token_ids.len();
+                // Two dynamic arrays: offsets, then the owners array followed by the ids one
+                let mut data = String::from("0x4e1273f4");
+                data.push_str(&Self::encode_uint(&BigUint::from(64u8)));
+                data.push_str(&Self::encode_uint(&BigUint::from(64 + 32 * (n + 1))));
+                data.push_str(&Self::encode_uint(&BigUint::from(n)));
+                data.push_str(&Self::encode_address(address).repeat(n));
+                data.push_str(&Self::encode_uint(&BigUint::from(n)));
+                for id in &token_ids {
+                    data.push_str(&Self::encode_uint(id));
+                }
+                let (balances_hex, wait_time) = self
+                    .eth_call_checked(&collection.address, data, rpc_index)
+                    .await;
+                (
+                    balances_hex.map(|x| {
+                        x.as_deref()
+                            .and_then(Self::decode_words)
+                            .and_then(|words| words.get(2..).map(|b| b.iter().sum()))
+                            // Contracts not following the standard hold none
+                            .unwrap_or_default()
+                    }),
+                    wait_time,
+                )
+            }
+        }
+    }
+}
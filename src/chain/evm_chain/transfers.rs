@@ -0,0 +1,146 @@
+use std::time::Duration;
+
+use itertools::Itertools;
+use num_bigint::BigUint;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::time::sleep;
+
+use crate::chain::{token::Token, transfer::Transfer};
+
+use super::EvmChain;
+
+/// Topic of `Transfer(address,address,uint256)`
+static TRANSFER_TOPIC: &str = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Log {
+    transaction_hash: String,
+    block_number: String,
+    topics: Vec<String>,
+    data: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct Block {
+    timestamp: String,
+}
+
+fn parse_hex(hex: &str) -> Option<u64> {
+    u64::from_str_radix(hex.strip_prefix("0x")?, 16).ok()
+}
+
+impl EvmChain {
+    /// Reads the logs matching the filter from the first block to the latest one. Public
+    /// RPCs reject queries over too many blocks or logs, so the block range is halved until
+    /// they are accepted
+    async fn get_logs(&self, address: &str, topics: Value, rpc_index: usize) -> Option<Vec<Log>> {
+        let latest_block = parse_hex(
+            &self
+                .rpc_call_json::<String>("eth_blockNumber", json!([]), rpc_index)
+                .await
+                .0?,
+        )?;
+        let mut logs = Vec::new();
+        let mut from_block = 0;
+        let mut range = latest_block + 1;
+        let mut rate_limited_count = 0;
+        while from_block <= latest_block {
+            let to_block = (from_block + range - 1).min(latest_block);
+            let params = json!([{
+                "address": address,
+                "fromBlock": format!("{from_block:#x}"),
+                "toBlock": format!("{to_block:#x}"),
+                "topics": topics,
+            }]);
+            let (response, wait_time) = self.rpc_request("eth_getLogs", params, rpc_index).await;
+            let response = response?;
+            if let Some(result) = response.get("result") {
+                logs.extend(serde_json::from_value::<Vec<Log>>(result.clone()).ok()?);
+                from_block = to_block + 1;
+                rate_limited_count = 0;
+                continue;
+            }
+            match wait_time {
+                // Rate limited, the same range is tried again
+                Some(wait_time) if rate_limited_count < 3 => {
+                    rate_limited_count += 1;
+                    sleep(Duration::from_secs_f32(wait_time.min(1.0))).await
+                }
+                Some(_) => return None,
+                None if range > 1 => range /= 2,
+                None => return None,
+            }
+        }
+        Some(logs)
+    }
+    /// Reads the ERC-20 `Transfer` logs of the tokens from and to the account. Native token
+    /// transfers emit no logs, so they can't be listed through a regular RPC
+    pub(super) async fn get_erc20_transfers(
+        &self,
+        address: &str,
+        tokens: &[Token],
+        rpc_index: usize,
+    ) -> Option<Vec<Transfer>> {
+        let account_topic = format!("0x{}", Self::encode_address(address));
+        let mut transfers = Vec::new();
+        for token in tokens {
+            for is_incoming in [true, false] {
+                let topics = if is_incoming {
+                    json!([TRANSFER_TOPIC, null, account_topic])
+                } else {
+                    json!([TRANSFER_TOPIC, account_topic])
+                };
+                let logs = self.get_logs(&token.address, topics, rpc_index).await?;
+                for log in logs {
+                    // ERC-721 transfers share the topic but index the token id as well
+                    if log.topics.len() != 3 {
+                        continue;
+                    }
+                    let counterparty = &log.topics[if is_incoming { 1 } else { 2 }];
+                    transfers.push((
+                        log.block_number,
+                        Transfer {
+                            hash: log.transaction_hash,
+                            timestamp: 0,
+                            token: token.clone(),
+                            amount: Self::decode_words(&log.data)?.first()?.clone(),
+                            is_incoming,
+                            counterparty: Some(Self::decode_address(&BigUint::parse_bytes(
+                                counterparty.strip_prefix("0x")?.as_bytes(),
+                                16,
+                            )?)),
+                        },
+                    ));
+                }
+            }
+        }
+        let block_numbers = transfers.iter().map(|(b, _)| b.clone()).unique();
+        let mut timestamps = Vec::new();
+        for block_number in block_numbers {
+            let block = self
+                .rpc_call_json::<Block>(
+                    "eth_getBlockByNumber",
+                    json!([block_number, false]),
+                    rpc_index,
+                )
+                .await
+                .0?;
+            timestamps.push((block_number, parse_hex(&block.timestamp)? as i64));
+        }
+        Some(
+            transfers
+                .into_iter()
+                .map(|(block_number, mut transfer)| {
+                    transfer.timestamp = timestamps
+                        .iter()
+                        .find(|(b, _)| *b == block_number)
+                        .map(|(_, timestamp)| *timestamp)
+                        .unwrap_or_default();
+                    transfer
+                })
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,293 @@
+use num_bigint::{BigInt, BigUint, Sign};
+use num_traits::{FromPrimitive, ToPrimitive};
+
+use crate::chain::position::LiquidityPosition;
+
+use super::{CallError, EvmChain};
+
+/// Fields of a position as returned by `positions(uint256)`
+struct PositionData {
+    token0: String,
+    token1: String,
+    fee: BigUint,
+    tick_lower: i32,
+    tick_upper: i32,
+    liquidity: BigUint,
+    fee_growth_inside0_last: BigUint,
+    fee_growth_inside1_last: BigUint,
+    tokens_owed0: BigUint,
+    tokens_owed1: BigUint,
+}
+
+/// Fee growth of a tick as returned by `ticks(int24)`
+struct TickData {
+    fee_growth_outside0: BigUint,
+    fee_growth_outside1: BigUint,
+}
+
+fn two_pow(exp: u32) -> BigUint {
+    BigUint::from(1u8) << exp
+}
+
+/// Subtraction modulo 2^256, as fee growth counters are allowed to overflow
+fn wrapping_sub(a: &BigUint, b: &BigUint) -> BigUint {
+    (a + two_pow(256) - b) % two_pow(256)
+}
+
+fn decode_int(word: &BigUint) -> Option<i32> {
+    if *word >= two_pow(255) {
+        return BigInt::from_biguint(Sign::Minus, two_pow(256) - word).to_i32();
+    }
+    word.to_i32()
+}
+
+fn encode_int(value: i32) -> String {
+    let word = if value < 0 {
+        two_pow(256) - BigUint::from(value.unsigned_abs())
+    } else {
+        BigUint::from(value as u32)
+    };
+    EvmChain::encode_uint(&word)
+}
+
+fn sqrt_price_at_tick(tick: i32) -> f64 {
+    1.0001_f64.powf(tick as f64 / 2.0)
+}
+
+/// Token amounts the liquidity is worth at the given price, following the Uniswap V3
+/// liquidity math
+fn get_amounts(
+    liquidity: &BigUint,
+    sqrt_price_x96: &BigUint,
+    tick_lower: i32,
+    tick_upper: i32,
+) -> Option<(BigUint, BigUint)> {
+    let liquidity = liquidity.to_f64()?;
+    let sqrt_price = sqrt_price_x96.to_f64()? / 2f64.powi(96);
+    let sqrt_lower = sqrt_price_at_tick(tick_lower);
+    let sqrt_upper = sqrt_price_at_tick(tick_upper);
+    let (amount0, amount1) = if sqrt_price <= sqrt_lower {
+        (
+            liquidity * (sqrt_upper - sqrt_lower) / (sqrt_lower * sqrt_upper),
+            0.0,
+        )
+    } else if sqrt_price < sqrt_upper {
+        (
+            liquidity * (sqrt_upper - sqrt_price) / (sqrt_price * sqrt_upper),
+            liquidity * (sqrt_price - sqrt_lower),
+        )
+    } else {
+        (0.0, liquidity * (sqrt_upper - sqrt_lower))
+    };
+    Some((
+        BigUint::from_f64(amount0.floor())?,
+        BigUint::from_f64(amount1.floor())?,
+    ))
+}
+
+impl EvmChain {
+    async fn get_position_data(
+        &self,
+        manager: &str,
+        id: &BigUint,
+        rpc_index: usize,
+    ) -> Result<PositionData, CallError> {
+        let data = format!("0x99fbab88{}", Self::encode_uint(id));
+        let words = self
+            .eth_call_words_checked(manager, data, rpc_index)
+            .await?;
+        if words.len() != 12 {
+            return Err(CallError::Reverted);
+        }
+        Ok(PositionData {
+            token0: Self::decode_address(&words[2]),
+            token1: Self::decode_address(&words[3]),
+            fee: words[4].clone(),
+            tick_lower: decode_int(&words[5]).ok_or(CallError::Reverted)?,
+            tick_upper: decode_int(&words[6]).ok_or(CallError::Reverted)?,
+            liquidity: words[7].clone(),
+            fee_growth_inside0_last: words[8].clone(),
+            fee_growth_inside1_last: words[9].clone(),
+            tokens_owed0: words[10].clone(),
+            tokens_owed1: words[11].clone(),
+        })
+    }
+    async fn get_tick_data(
+        &self,
+        pool: &str,
+        tick: i32,
+        rpc_index: usize,
+    ) -> Result<TickData, CallError> {
+        let data = format!("0xf30dba93{}", encode_int(tick));
+        let words = self.eth_call_words_checked(pool, data, rpc_index).await?;
+        match (words.get(2), words.get(3)) {
+            (Some(outside0), Some(outside1)) => Ok(TickData {
+                fee_growth_outside0: outside0.clone(),
+                fee_growth_outside1: outside1.clone(),
+            }),
+            _ => Err(CallError::Reverted),
+        }
+    }
+    async fn get_single_word(
+        &self,
+        to: &str,
+        data: String,
+        rpc_index: usize,
+    ) -> Result<BigUint, CallError> {
+        self.eth_call_words_checked(to, data, rpc_index)
+            .await?
+            .first()
+            .cloned()
+            .ok_or(CallError::Reverted)
+    }
+    async fn get_position(
+        &self,
+        manager: &str,
+        factory: &str,
+        id: BigUint,
+        rpc_index: usize,
+    ) -> Result<LiquidityPosition, CallError> {
+        let position = self.get_position_data(manager, &id, rpc_index).await?;
+        let pool_data = format!(
+            "0x1698ee82{}{}{}",
+            Self::encode_address(&position.token0),
+            Self::encode_address(&position.token1),
+            Self::encode_uint(&position.fee)
+        );
+        let pool =
+            Self::decode_address(&self.get_single_word(factory, pool_data, rpc_index).await?);
+        let slot0 = self
+            .eth_call_words_checked(&pool, "0x3850c7bd".to_string(), rpc_index)
+            .await?;
+        let (sqrt_price_x96, tick) = match slot0.as_slice() {
+            [sqrt_price_x96, tick, ..] => {
+                (sqrt_price_x96, decode_int(tick).ok_or(CallError::Reverted)?)
+            }
+            _ => return Err(CallError::Reverted),
+        };
+        let (amount0, amount1) = get_amounts(
+            &position.liquidity,
+            sqrt_price_x96,
+            position.tick_lower,
+            position.tick_upper,
+        )
+        .ok_or(CallError::Reverted)?;
+
+        let fee_growth_global0 = self
+            .get_single_word(&pool, "0xf3058399".to_string(), rpc_index)
+            .await?;
+        let fee_growth_global1 = self
+            .get_single_word(&pool, "0x46141319".to_string(), rpc_index)
+            .await?;
+        let lower = self
+            .get_tick_data(&pool, position.tick_lower, rpc_index)
+            .await?;
+        let upper = self
+            .get_tick_data(&pool, position.tick_upper, rpc_index)
+            .await?;
+        // Fee growth inside the range is the global growth minus the growth that happened
+        // below the lower tick and above the upper tick
+        let fee_growth_inside = |global: &BigUint, lower: &BigUint, upper: &BigUint| {
+            let below = if tick >= position.tick_lower {
+                lower.clone()
+            } else {
+                wrapping_sub(global, lower)
+            };
+            let above = if tick < position.tick_upper {
+                upper.clone()
+            } else {
+                wrapping_sub(global, upper)
+            };
+            wrapping_sub(&wrapping_sub(global, &below), &above)
+        };
+        let fee_growth_inside0 = fee_growth_inside(
+            &fee_growth_global0,
+            &lower.fee_growth_outside0,
+            &upper.fee_growth_outside0,
+        );
+        let fee_growth_inside1 = fee_growth_inside(
+            &fee_growth_global1,
+            &lower.fee_growth_outside1,
+            &upper.fee_growth_outside1,
+        );
+        let fees0 = &position.tokens_owed0
+            + &position.liquidity
+                * wrapping_sub(&fee_growth_inside0, &position.fee_growth_inside0_last)
+                / two_pow(128);
+        let fees1 = &position.tokens_owed1
+            + &position.liquidity
+                * wrapping_sub(&fee_growth_inside1, &position.fee_growth_inside1_last)
+                / two_pow(128);
+
+        let token0 = self
+            .fetch_token_checked(&position.token0, rpc_index)
+            .await?;
+        let token1 = self
+            .fetch_token_checked(&position.token1, rpc_index)
+            .await?;
+        Ok(LiquidityPosition {
+            manager: manager.to_string(),
+            id,
+            token0,
+            token1,
+            amount0,
+            amount1,
+            fees0,
+            fees1,
+            in_range: position.tick_lower <= tick && tick < position.tick_upper,
+        })
+    }
+    async fn get_positions(
+        &self,
+        manager: &str,
+        address: &str,
+        rpc_index: usize,
+    ) -> Result<Vec<LiquidityPosition>, CallError> {
+        let balance_data = format!("0x70a08231{}", Self::encode_address(address));
+        let count = self
+            .get_single_word(manager, balance_data, rpc_index)
+            .await?
+            .to_usize()
+            .ok_or(CallError::Reverted)?;
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        let factory = Self::decode_address(
+            &self
+                .get_single_word(manager, "0xc45a0155".to_string(), rpc_index)
+                .await?,
+        );
+        let mut positions = Vec::new();
+        for i in 0..count {
+            let id_data = format!(
+                "0x2f745c59{}{}",
+                Self::encode_address(address),
+                Self::encode_uint(&BigUint::from(i))
+            );
+            let id = self.get_single_word(manager, id_data, rpc_index).await?;
+            match self.get_position(manager, &factory, id, rpc_index).await {
+                Ok(position) if !position.is_empty() => positions.push(position),
+                Ok(_) => {}
+                // Positions that can not be read, e.g. of spam tokens or pools that no longer
+                // exist, are left out instead of failing the whole account
+                Err(CallError::Reverted) => {}
+                Err(CallError::Failed) => return Err(CallError::Failed),
+            }
+        }
+        Ok(positions)
+    }
+    /// Enumerates the position NFTs the account holds in a NonfungiblePositionManager,
+    /// returning None only when a request fails
+    pub(super) async fn get_uniswap_v3_positions(
+        &self,
+        manager: &str,
+        address: &str,
+        rpc_index: usize,
+    ) -> Option<Vec<LiquidityPosition>> {
+        match self.get_positions(manager, address, rpc_index).await {
+            Ok(x) => Some(x),
+            Err(CallError::Reverted) => Some(Vec::new()),
+            Err(CallError::Failed) => None,
+        }
+    }
+}
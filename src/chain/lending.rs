@@ -0,0 +1,17 @@
+use num_bigint::BigUint;
+
+use super::token::Token;
+
+/// Account position in an Aave-style lending pool
+#[derive(Debug, Clone)]
+pub struct LendingPosition {
+    /// Supplied tokens and their amounts
+    pub collateral: Vec<(Token, BigUint)>,
+    /// Borrowed tokens and their amounts
+    pub debt: Vec<(Token, BigUint)>,
+    /// Addresses of the tokens received for the collateral, e.g. aTokens, which already
+    /// account for it when held as a tracked token
+    pub collateral_tokens: Vec<String>,
+    /// Ratio at which the position gets liquidated when below 1, none when there's no debt
+    pub health_factor: Option<f64>,
+}
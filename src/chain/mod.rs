@@ -1,20 +1,28 @@
 pub mod chain_properties;
 pub mod chain_type;
 pub mod evm_chain;
+pub mod lending;
+pub mod nft;
+pub mod position;
 pub mod sol_chain;
 pub mod token;
 pub mod ton_chain;
+pub mod transfer;
 
 use std::str::FromStr;
 
 use chain_properties::ChainProperties;
 use chain_type::ChainType;
 use evm_chain::EvmChain;
+use lending::LendingPosition;
+use nft::{Nft, NftCollection};
 use num_bigint::BigUint;
+use position::LiquidityPosition;
 use reqwest::{header::HeaderMap, Client, Url};
 use sol_chain::SolChain;
-use token::Token;
+use token::{Token, TokenKind};
 use ton_chain::TonChain;
+use transfer::{Transfer, TransferHistory};
 
 use crate::{dexscreener, utils::support_option::SupportOption};
 
@@ -39,6 +47,9 @@ impl Chain {
             rpc_urls: rpc_urls.iter().map(|u| Url::from_str(u).unwrap()).collect(),
             rpc_headers: HeaderMap::new(),
             name: name.to_string(),
+            eip1191_chain_id: None,
+            position_managers: Vec::new(),
+            lending_pools: Vec::new(),
             native_token: Token::hardcode(
                 native_token_symbol,
                 native_token_address,
@@ -52,6 +63,16 @@ impl Chain {
             http_client: Client::new(),
         }
     }
+    /// Registers a Uniswap V3 compatible NonfungiblePositionManager deployed on the chain
+    pub fn with_position_manager(mut self, address: &str) -> Self {
+        self.properties.position_managers.push(address.to_string());
+        self
+    }
+    /// Registers an Aave V3 compatible lending pool deployed on the chain
+    pub fn with_lending_pool(mut self, address: &str) -> Self {
+        self.properties.lending_pools.push(address.to_string());
+        self
+    }
 }
 
 pub trait ChainOps {
@@ -68,8 +89,24 @@ pub trait ChainOps {
     ) -> (Option<BigUint>, Option<f32>);
     async fn get_token_decimals(&self, token_address: &str, rpc_index: usize) -> Option<usize>;
     async fn get_token_symbol(&self, token_address: &str, _rpc_index: usize) -> Option<String> {
-        let pairs = dexscreener::pairs::get_pairs(vec![token_address], vec![]).await?;
-        (!pairs.is_empty()).then(|| pairs[0].base_token.symbol.clone())
+        dexscreener::pairs::get_token_symbol(token_address).await
+    }
+    /// Detects tokens that represent a claim on other tokens, such as vault shares
+    async fn get_token_kind(
+        &self,
+        _token_address: &str,
+        _rpc_index: usize,
+    ) -> SupportOption<TokenKind> {
+        SupportOption::Unsupported
+    }
+    /// Converts a balance of a non-standard token into balances of the tokens backing it
+    async fn get_underlying_balances(
+        &self,
+        _token: &Token,
+        _balance: &BigUint,
+        _rpc_index: usize,
+    ) -> (Option<Vec<(Token, BigUint)>>, Option<f32>) {
+        (Some(Vec::new()), None)
     }
     async fn get_holdings_balance(
         &self,
@@ -77,7 +114,60 @@ pub trait ChainOps {
         rpc_index: usize,
     ) -> SupportOption<Vec<(String, BigUint)>>;
     async fn scan_for_tokens(&self, address: &str, rpc_index: usize) -> SupportOption<Vec<Token>>;
+    /// Lists the concentrated liquidity positions held by the account
+    async fn get_liquidity_positions(
+        &self,
+        _address: &str,
+        _rpc_index: usize,
+    ) -> SupportOption<Vec<LiquidityPosition>> {
+        SupportOption::Unsupported
+    }
+    /// Lists the lending pool positions of the account
+    async fn get_lending_positions(
+        &self,
+        _address: &str,
+        _rpc_index: usize,
+    ) -> SupportOption<Vec<LendingPosition>> {
+        SupportOption::Unsupported
+    }
+    /// Detects the standard and name of an NFT collection
+    async fn get_nft_collection(
+        &self,
+        _address: &str,
+        _token_ids: Vec<String>,
+        _rpc_index: usize,
+    ) -> SupportOption<NftCollection> {
+        SupportOption::Unsupported
+    }
+    /// Counts the tokens of the collection owned by the account
+    async fn get_nft_count(
+        &self,
+        _collection: &NftCollection,
+        _address: &str,
+        _rpc_index: usize,
+    ) -> (Option<BigUint>, Option<f32>) {
+        (Some(BigUint::ZERO), None)
+    }
+    /// Lists every NFT owned by the account, for chains where they can be discovered
+    async fn list_nfts(&self, _address: &str, _rpc_index: usize) -> SupportOption<Vec<Nft>> {
+        SupportOption::Unsupported
+    }
+    /// Lists the transfers of the native token and the given tokens in and out of the
+    /// account, returning none when the history could not be fetched from the RPC
+    async fn get_transfers(
+        &self,
+        address: &str,
+        tokens: &[Token],
+        rpc_index: usize,
+    ) -> SupportOption<TransferHistory>;
     fn parse_wallet_address(&self, address: &str) -> Option<String>;
+    /// Like `parse_wallet_address` but explains why the address was rejected
+    fn validate_wallet_address(&self, address: &str) -> Result<String, String> {
+        match self.parse_wallet_address(address) {
+            Some(x) => Ok(x),
+            None => Err("invalid address format".to_string()),
+        }
+    }
     fn parse_token_address(&self, address: &str) -> Option<String> {
         self.parse_wallet_address(address)
     }
@@ -129,12 +219,71 @@ impl ChainOps for Chain {
     async fn get_token_symbol(&self, token_address: &str, rpc_index: usize) -> Option<String> {
         chain_ops_method!(self, get_token_symbol, token_address, rpc_index; await)
     }
+    async fn get_token_kind(
+        &self,
+        token_address: &str,
+        rpc_index: usize,
+    ) -> SupportOption<TokenKind> {
+        chain_ops_method!(self, get_token_kind, token_address, rpc_index; await)
+    }
+    async fn get_underlying_balances(
+        &self,
+        token: &Token,
+        balance: &BigUint,
+        rpc_index: usize,
+    ) -> (Option<Vec<(Token, BigUint)>>, Option<f32>) {
+        chain_ops_method!(self, get_underlying_balances, token, balance, rpc_index; await)
+    }
     async fn scan_for_tokens(&self, address: &str, rpc_index: usize) -> SupportOption<Vec<Token>> {
         chain_ops_method!(self, scan_for_tokens, address, rpc_index; await)
     }
+    async fn get_liquidity_positions(
+        &self,
+        address: &str,
+        rpc_index: usize,
+    ) -> SupportOption<Vec<LiquidityPosition>> {
+        chain_ops_method!(self, get_liquidity_positions, address, rpc_index; await)
+    }
+    async fn get_lending_positions(
+        &self,
+        address: &str,
+        rpc_index: usize,
+    ) -> SupportOption<Vec<LendingPosition>> {
+        chain_ops_method!(self, get_lending_positions, address, rpc_index; await)
+    }
+    async fn get_nft_collection(
+        &self,
+        address: &str,
+        token_ids: Vec<String>,
+        rpc_index: usize,
+    ) -> SupportOption<NftCollection> {
+        chain_ops_method!(self, get_nft_collection, address, token_ids, rpc_index; await)
+    }
+    async fn get_nft_count(
+        &self,
+        collection: &NftCollection,
+        address: &str,
+        rpc_index: usize,
+    ) -> (Option<BigUint>, Option<f32>) {
+        chain_ops_method!(self, get_nft_count, collection, address, rpc_index; await)
+    }
+    async fn list_nfts(&self, address: &str, rpc_index: usize) -> SupportOption<Vec<Nft>> {
+        chain_ops_method!(self, list_nfts, address, rpc_index; await)
+    }
+    async fn get_transfers(
+        &self,
+        address: &str,
+        tokens: &[Token],
+        rpc_index: usize,
+    ) -> SupportOption<TransferHistory> {
+        chain_ops_method!(self, get_transfers, address, tokens, rpc_index; await)
+    }
     fn parse_wallet_address(&self, address: &str) -> Option<String> {
         chain_ops_method!(self, parse_wallet_address, address)
     }
+    fn validate_wallet_address(&self, address: &str) -> Result<String, String> {
+        chain_ops_method!(self, validate_wallet_address, address)
+    }
     fn parse_token_address(&self, address: &str) -> Option<String> {
         chain_ops_method!(self, parse_token_address, address)
     }
@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum NftStandard {
+    Erc721,
+    Erc1155,
+}
+
+impl NftStandard {
+    pub fn label(&self) -> String {
+        match self {
+            Self::Erc721 => "ERC-721",
+            Self::Erc1155 => "ERC-1155",
+        }
+        .to_string()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NftCollection {
+    pub name: String,
+    pub address: String,
+    pub standard: NftStandard,
+    /// Token ids to look for, required by ERC-1155 and optional for ERC-721
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub token_ids: Vec<String>,
+    /// Fixed floor price set by the user, takes precedence over the floor price provider
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub floor_usd: Option<f64>,
+}
+
+/// Single NFT owned by an account
+#[derive(Debug, Clone)]
+pub struct Nft {
+    /// Mint or item address of the NFT
+    pub address: String,
+    pub name: String,
+    /// Collection name or address, if the NFT belongs to one
+    pub collection: Option<String>,
+}
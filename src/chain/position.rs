@@ -0,0 +1,31 @@
+use num_bigint::BigUint;
+
+use super::token::Token;
+
+/// Concentrated liquidity position, such as a Uniswap V3 position NFT
+#[derive(Debug, Clone)]
+pub struct LiquidityPosition {
+    /// Address of the contract that minted the position
+    pub manager: String,
+    pub id: BigUint,
+    pub token0: Token,
+    pub token1: Token,
+    /// Token amounts the liquidity is currently worth
+    pub amount0: BigUint,
+    pub amount1: BigUint,
+    /// Fees earned but not collected yet
+    pub fees0: BigUint,
+    pub fees1: BigUint,
+    pub in_range: bool,
+}
+
+impl LiquidityPosition {
+    pub fn label(&self) -> String {
+        format!("{}/{} #{}", self.token0.symbol, self.token1.symbol, self.id)
+    }
+    pub fn is_empty(&self) -> bool {
+        [&self.amount0, &self.amount1, &self.fees0, &self.fees1]
+            .iter()
+            .all(|x| **x == BigUint::ZERO)
+    }
+}
@@ -1,3 +1,6 @@
+mod metaplex;
+mod transfers;
+
 use std::str::FromStr;
 
 use base58::FromBase58;
@@ -90,6 +93,8 @@ struct SolSplToken {
     mint: String,
     #[query(".account.data.parsed.info.tokenAmount.decimals")]
     decimals: u64,
+    #[query(".account.data.parsed.info.tokenAmount.amount")]
+    amount: String,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -98,6 +103,18 @@ struct SolGetTokenAccountsResponse {
     value: Vec<SolSplToken>,
 }
 
+#[derive(serde::Deserialize, Debug, Clone)]
+struct SolAccountInfo {
+    /// Encoded data and its encoding
+    data: (String, String),
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct SolGetMultipleAccountsResponse {
+    #[query(".result.value")]
+    value: Vec<Option<SolAccountInfo>>,
+}
+
 impl ChainOps for SolChain {
     async fn get_native_token_balance(
         &self,
@@ -178,12 +195,79 @@ impl ChainOps for SolChain {
                             address: token.mint.clone(),
                             decimals: token.decimals as usize,
                             symbol: pair.base_token.symbol.clone(),
+                            kind: TokenKind::Standard,
                         })
                     })
                 })
                 .collect(),
         )
     }
+    async fn list_nfts(&self, address: &str, rpc_index: usize) -> SupportOption<Vec<Nft>> {
+        let params = json!([
+            address,
+            { "programId": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA" },
+            { "encoding": "jsonParsed" },
+        ]);
+        // NFTs are tokens with no decimals of which the account holds the single unit
+        let mints = self
+            .rpc_call::<SolGetTokenAccountsResponse>("getTokenAccountsByOwner", params, rpc_index)
+            .await
+            .0
+            .to_supported()?
+            .value
+            .into_iter()
+            .filter(|token| token.decimals == 0 && token.amount == "1")
+            .map(|token| token.mint)
+            .collect::<Vec<_>>();
+        let mut nfts = Vec::new();
+        // Accounts can be fetched up to 100 at a time
+        for mints in mints.chunks(100) {
+            let metadata_addresses = mints
+                .iter()
+                .map(|mint| metaplex::find_metadata_address(mint))
+                .collect::<Option<Vec<_>>>()
+                .to_supported()?;
+            let params = json!([metadata_addresses, { "encoding": "base64" }]);
+            let accounts = self
+                .rpc_call::<SolGetMultipleAccountsResponse>(
+                    "getMultipleAccounts",
+                    params,
+                    rpc_index,
+                )
+                .await
+                .0
+                .to_supported()?
+                .value;
+            nfts.extend(mints.iter().zip(accounts).map(|(mint, account)| {
+                let metadata = account.and_then(|a| metaplex::decode_metadata(&a.data.0));
+                Nft {
+                    address: mint.clone(),
+                    name: metadata
+                        .as_ref()
+                        .map(|m| {
+                            if m.symbol.is_empty() {
+                                m.name.clone()
+                            } else {
+                                format!("{} ({})", m.name, m.symbol)
+                            }
+                        })
+                        .unwrap_or("Unknown".to_string()),
+                    collection: metadata.and_then(|m| m.collection),
+                }
+            }));
+        }
+        SupportOption::SupportedSome(nfts)
+    }
+    async fn get_transfers(
+        &self,
+        address: &str,
+        tokens: &[Token],
+        rpc_index: usize,
+    ) -> SupportOption<TransferHistory> {
+        self.get_balance_transfers(address, tokens, rpc_index)
+            .await
+            .into()
+    }
     fn parse_wallet_address(&self, address: &str) -> Option<String> {
         let address_b58 = SolChain::to_b58(address)?;
         CompressedEdwardsY::from_slice(&address_b58)
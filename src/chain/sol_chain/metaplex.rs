@@ -0,0 +1,107 @@
+use base58::{FromBase58, ToBase58};
+use base64::{prelude::BASE64_STANDARD, Engine};
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use sha2::{Digest, Sha256};
+
+static TOKEN_METADATA_PROGRAM: &str = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bgpdjjt9";
+
+/// Fields of a Metaplex metadata account relevant for display
+pub struct Metadata {
+    pub name: String,
+    pub symbol: String,
+    pub collection: Option<String>,
+}
+
+/// Derives the program address of the metadata account of a mint, which is the first
+/// off-curve hash of the seeds when trying bumps from 255 down
+pub fn find_metadata_address(mint: &str) -> Option<String> {
+    let program = TOKEN_METADATA_PROGRAM.from_base58().ok()?;
+    let mint = mint.from_base58().ok()?;
+    (0..=255u8).rev().find_map(|bump| {
+        let mut hasher = Sha256::new();
+        hasher.update(b"metadata");
+        hasher.update(&program);
+        hasher.update(&mint);
+        hasher.update([bump]);
+        hasher.update(&program);
+        hasher.update(b"ProgramDerivedAddress");
+        let hash = hasher.finalize();
+        let is_on_curve = CompressedEdwardsY::from_slice(&hash)
+            .ok()
+            .and_then(|p| p.decompress())
+            .is_some();
+        (!is_on_curve).then(|| hash.to_base58())
+    })
+}
+
+/// Reads Borsh encoded data sequentially
+struct BorshReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl BorshReader<'_> {
+    fn read(&mut self, len: usize) -> Option<&[u8]> {
+        let bytes = self.data.get(self.offset..self.offset + len)?;
+        self.offset += len;
+        Some(bytes)
+    }
+    fn read_u8(&mut self) -> Option<u8> {
+        Some(self.read(1)?[0])
+    }
+    fn read_u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.read(4)?.try_into().ok()?))
+    }
+    fn read_string(&mut self) -> Option<String> {
+        let len = self.read_u32()? as usize;
+        // Strings are padded with null bytes to a fixed length
+        Some(
+            String::from_utf8_lossy(self.read(len)?)
+                .trim_end_matches('\0')
+                .trim()
+                .to_string(),
+        )
+    }
+    fn read_option(&mut self, len: usize) -> Option<Option<&[u8]>> {
+        match self.read_u8()? {
+            0 => Some(None),
+            _ => Some(Some(self.read(len)?)),
+        }
+    }
+}
+
+/// Decodes a base64 encoded metadata account
+pub fn decode_metadata(data: &str) -> Option<Metadata> {
+    let data = BASE64_STANDARD.decode(data).ok()?;
+    let mut reader = BorshReader {
+        data: &data,
+        offset: 0,
+    };
+    // Key, update authority and mint
+    reader.read(1 + 32 + 32)?;
+    let name = reader.read_string()?;
+    let symbol = reader.read_string()?;
+    let _uri = reader.read_string()?;
+    // Seller fee basis points
+    reader.read(2)?;
+    if reader.read_u8()? == 1 {
+        let creators = reader.read_u32()? as usize;
+        // Address, verified and share
+        reader.read(creators * (32 + 1 + 1))?;
+    }
+    // Primary sale happened and is mutable
+    reader.read(2)?;
+    let collection = (|| {
+        // Edition nonce and token standard
+        reader.read_option(1)?;
+        reader.read_option(1)?;
+        // Verified flag followed by the collection mint
+        let collection = reader.read_option(1 + 32)?;
+        Some(collection?[1..].to_base58())
+    })();
+    Some(Metadata {
+        name,
+        symbol,
+        collection,
+    })
+}
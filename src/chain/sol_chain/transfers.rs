@@ -0,0 +1,229 @@
+use std::time::Duration;
+
+use itertools::Itertools;
+use num_bigint::BigUint;
+use serde_json::{json, Value};
+use serde_query::Deserialize;
+use tokio::time::sleep;
+
+use crate::chain::{
+    token::Token,
+    transfer::{Transfer, TransferHistory},
+};
+
+use super::SolChain;
+
+/// Signatures can be fetched up to 1000 at a time
+static SIGNATURES_LIMIT: usize = 1000;
+
+#[derive(serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct SolSignature {
+    signature: String,
+    block_time: Option<i64>,
+    err: Option<Value>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct SolGetSignaturesResponse {
+    #[query(".result")]
+    signatures: Vec<SolSignature>,
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+struct SolTokenAmount {
+    amount: String,
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct SolTokenBalance {
+    mint: String,
+    owner: Option<String>,
+    ui_token_amount: SolTokenAmount,
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct SolTransactionMeta {
+    fee: u64,
+    pre_balances: Vec<u64>,
+    post_balances: Vec<u64>,
+    #[serde(default)]
+    pre_token_balances: Vec<SolTokenBalance>,
+    #[serde(default)]
+    post_token_balances: Vec<SolTokenBalance>,
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+struct SolAccountKey {
+    pubkey: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct SolGetTransactionResponse {
+    #[query(".result.meta")]
+    meta: SolTransactionMeta,
+    #[query(".result.transaction.message.accountKeys")]
+    account_keys: Vec<SolAccountKey>,
+}
+
+impl SolChain {
+    async fn get_transaction(
+        &self,
+        signature: &str,
+        rpc_index: usize,
+    ) -> Option<SolGetTransactionResponse> {
+        let params = json!([
+            signature,
+            { "encoding": "jsonParsed", "maxSupportedTransactionVersion": 0 },
+        ]);
+        // Transactions are fetched one by one, so rate limits are likely to be hit
+        for _ in 0..3 {
+            let (transaction, wait_time) = self
+                .rpc_call::<SolGetTransactionResponse>("getTransaction", params.clone(), rpc_index)
+                .await;
+            if transaction.is_some() {
+                return transaction;
+            }
+            sleep(Duration::from_secs_f32(wait_time.unwrap_or(0.5).min(1.0))).await;
+        }
+        None
+    }
+    /// Derives the transfers of the account in a transaction from its balance changes. The
+    /// counterparty is the other account whose balance changed by the opposite amount
+    fn get_transaction_transfers(
+        &self,
+        address: &str,
+        tokens: &[Token],
+        signature: &SolSignature,
+        transaction: &SolGetTransactionResponse,
+    ) -> Option<Vec<Transfer>> {
+        let meta = &transaction.meta;
+        let mut deltas = Vec::new();
+        let native_deltas = transaction
+            .account_keys
+            .iter()
+            .enumerate()
+            .map(|(i, key)| {
+                let mut delta =
+                    *meta.post_balances.get(i)? as i128 - *meta.pre_balances.get(i)? as i128;
+                // The fee payer is always the first account, fees are not transfers
+                if i == 0 {
+                    delta += meta.fee as i128;
+                }
+                Some((key.pubkey.as_str(), delta))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        if let Some((_, delta)) = native_deltas.iter().find(|(key, _)| *key == address) {
+            deltas.push((&self.properties.native_token, *delta, native_deltas.clone()));
+        }
+        for token in tokens {
+            let token_balance = |balances: &[SolTokenBalance], owner: &str| {
+                balances
+                    .iter()
+                    .filter(|b| b.mint == token.address && b.owner.as_deref() == Some(owner))
+                    .filter_map(|b| b.ui_token_amount.amount.parse::<i128>().ok())
+                    .sum::<i128>()
+            };
+            let token_deltas = meta
+                .pre_token_balances
+                .iter()
+                .chain(&meta.post_token_balances)
+                .filter(|b| b.mint == token.address)
+                .filter_map(|b| b.owner.as_deref())
+                .unique()
+                .map(|owner| {
+                    (
+                        owner,
+                        token_balance(&meta.post_token_balances, owner)
+                            - token_balance(&meta.pre_token_balances, owner),
+                    )
+                })
+                .collect::<Vec<_>>();
+            if let Some((_, delta)) = token_deltas.iter().find(|(owner, _)| *owner == address) {
+                deltas.push((token, *delta, token_deltas.clone()));
+            }
+        }
+        Some(
+            deltas
+                .into_iter()
+                .filter(|(_, delta, _)| *delta != 0)
+                .map(|(token, delta, all_deltas)| Transfer {
+                    hash: signature.signature.clone(),
+                    timestamp: signature.block_time.unwrap_or_default(),
+                    token: token.clone(),
+                    amount: BigUint::from(delta.unsigned_abs()),
+                    is_incoming: delta > 0,
+                    counterparty: all_deltas
+                        .iter()
+                        .find(|(key, other_delta)| *key != address && *other_delta == -delta)
+                        .map(|(key, _)| key.to_string()),
+                })
+                .collect(),
+        )
+    }
+    /// Lists the signatures of every transaction of the account, paging back from the most
+    /// recent one
+    async fn get_signatures(&self, address: &str, rpc_index: usize) -> Option<Vec<SolSignature>> {
+        let mut signatures: Vec<SolSignature> = Vec::new();
+        loop {
+            let mut options = json!({ "limit": SIGNATURES_LIMIT });
+            if let Some(last) = signatures.last() {
+                options["before"] = json!(last.signature);
+            }
+            let page = self
+                .rpc_call::<SolGetSignaturesResponse>(
+                    "getSignaturesForAddress",
+                    json!([address, options]),
+                    rpc_index,
+                )
+                .await
+                .0?
+                .signatures;
+            let is_last_page = page.len() < SIGNATURES_LIMIT;
+            signatures.extend(page);
+            if is_last_page {
+                return Some(signatures);
+            }
+        }
+    }
+    /// Derives the transfers of the account from the balance changes of its transactions,
+    /// leaving out the transactions that could not be read
+    pub(super) async fn get_balance_transfers(
+        &self,
+        address: &str,
+        tokens: &[Token],
+        rpc_index: usize,
+    ) -> Option<TransferHistory> {
+        let signatures = self.get_signatures(address, rpc_index).await?;
+        let signatures = signatures
+            .iter()
+            .filter(|s| s.err.is_none())
+            .collect::<Vec<_>>();
+        let mut transfers = Vec::new();
+        let mut skipped_count = 0;
+        for signature in &signatures {
+            let transaction_transfers =
+                match self.get_transaction(&signature.signature, rpc_index).await {
+                    Some(transaction) => {
+                        self.get_transaction_transfers(address, tokens, signature, &transaction)
+                    }
+                    None => None,
+                };
+            match transaction_transfers {
+                Some(x) => transfers.extend(x),
+                None => skipped_count += 1,
+            }
+        }
+        // Nothing could be read, the RPC is likely down
+        if skipped_count > 0 && skipped_count == signatures.len() {
+            return None;
+        }
+        Some(TransferHistory {
+            transfers,
+            skipped_count,
+            is_truncated: false,
+        })
+    }
+}
@@ -1,13 +1,47 @@
 use num_bigint::BigUint;
 use serde::{Deserialize, Serialize};
 
+use crate::utils::support_option::SupportOption;
+
 use super::{Chain, ChainOps};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Describes how a token should be valued
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub enum TokenKind {
+    /// Valued through its own price
+    #[default]
+    Standard,
+    /// ERC-4626 vault share, valued through the underlying asset it can be redeemed for
+    Vault { asset: Box<Token> },
+    /// Uniswap V2 compatible LP token, valued through its share of the pair reserves
+    LiquidityPair {
+        token0: Box<Token>,
+        token1: Box<Token>,
+    },
+}
+
+impl TokenKind {
+    pub fn is_standard(&self) -> bool {
+        *self == Self::Standard
+    }
+    pub fn label(&self) -> String {
+        match self {
+            Self::Standard => "-".to_string(),
+            Self::Vault { asset } => format!("Vault ({})", asset.symbol),
+            Self::LiquidityPair { token0, token1 } => {
+                format!("LP ({}/{})", token0.symbol, token1.symbol)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Token {
     pub symbol: String,
     pub address: String,
     pub decimals: usize,
+    #[serde(default, skip_serializing_if = "TokenKind::is_standard")]
+    pub kind: TokenKind,
 }
 
 impl Token {
@@ -16,15 +50,21 @@ impl Token {
             symbol: symbol.to_string(),
             address: address.to_string(),
             decimals,
+            kind: TokenKind::Standard,
         }
     }
     pub async fn new(address: &str, chain: &Chain) -> Option<Self> {
         let decimals = chain.get_token_decimals(address, 0).await?;
         let symbol = chain.get_token_symbol(address, 0).await?;
+        let kind = match chain.get_token_kind(address, 0).await {
+            SupportOption::SupportedSome(x) => x,
+            _ => TokenKind::Standard,
+        };
         Some(Self {
             symbol,
             address: chain.parse_token_address(address)?,
             decimals,
+            kind,
         })
     }
     pub fn format(&self, value: &BigUint) -> f64 {
@@ -1,5 +1,6 @@
 use serde::Deserialize;
-use std::str::FromStr;
+use std::{str::FromStr, time::Duration};
+use tokio::time::sleep;
 use tonlib_core::TonAddress;
 
 use num_bigint::BigUint;
@@ -11,7 +12,10 @@ use crate::utils::{
     support_option::{SupportOption, ToSupported},
 };
 
-use super::{Chain, ChainOps, ChainProperties, Token};
+use super::{Chain, ChainOps, ChainProperties, Nft, Token, TokenKind, Transfer, TransferHistory};
+
+/// Events are fetched 100 at a time, up to this many pages
+static EVENT_PAGES_LIMIT: usize = 10;
 
 #[derive(Debug)]
 pub struct TonChain {
@@ -61,6 +65,71 @@ struct TonGetJettonInfo {
     metadata: JettonMetadata,
 }
 
+#[derive(Deserialize, Debug, Clone)]
+struct TonNftCollection {
+    address: String,
+    name: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+struct TonNftMetadata {
+    name: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct TonNftItem {
+    address: String,
+    collection: Option<TonNftCollection>,
+    #[serde(default)]
+    metadata: TonNftMetadata,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct TonGetAccountNftsResponse {
+    nft_items: Vec<TonNftItem>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct TonAccountAddress {
+    address: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct TonTransferAction {
+    sender: TonAccountAddress,
+    recipient: TonAccountAddress,
+    amount: u64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct TonJettonTransferAction {
+    sender: Option<TonAccountAddress>,
+    recipient: Option<TonAccountAddress>,
+    amount: String,
+    jetton: TonJetton,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[allow(non_snake_case)]
+struct TonAction {
+    status: String,
+    TonTransfer: Option<TonTransferAction>,
+    JettonTransfer: Option<TonJettonTransferAction>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct TonEvent {
+    event_id: String,
+    timestamp: i64,
+    actions: Vec<TonAction>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct TonGetAccountEventsResponse {
+    events: Vec<TonEvent>,
+    next_from: i64,
+}
+
 impl TonChain {
     fn parse_address_to_base64(address: &str, is_token: bool) -> Option<String> {
         TonAddress::from_base64_url(address)
@@ -170,11 +239,115 @@ impl ChainOps for TonChain {
                 address: self.parse_token_address(&b.jetton.address)?,
                 symbol: b.jetton.symbol.clone(),
                 decimals: b.jetton.decimals,
+                kind: TokenKind::Standard,
             })
         })
         .collect::<Option<_>>()
         .into()
     }
+    async fn list_nfts(&self, address: &str, _rpc_index: usize) -> SupportOption<Vec<Nft>> {
+        let address = self.parse_wallet_address(address).to_supported()?;
+        SupportOption::SupportedSome(
+            self.api_call::<TonGetAccountNftsResponse>(
+                format!("accounts/{address}/nfts"),
+                vec![("limit", "1000"), ("indirect_ownership", "false")],
+            )
+            .await
+            .0
+            .to_supported()?
+            .nft_items
+            .into_iter()
+            .map(|item| Nft {
+                address: Self::parse_address_to_base64(&item.address, true).unwrap_or(item.address),
+                name: item.metadata.name.unwrap_or("Unknown".to_string()),
+                collection: item.collection.map(|c| c.name.unwrap_or(c.address)),
+            })
+            .collect(),
+        )
+    }
+    async fn get_transfers(
+        &self,
+        address: &str,
+        tokens: &[Token],
+        _rpc_index: usize,
+    ) -> SupportOption<TransferHistory> {
+        let address = self.parse_wallet_address(address).to_supported()?;
+        let mut transfers = Vec::new();
+        let mut before_lt = None;
+        let mut is_truncated = false;
+        for page in 0..EVENT_PAGES_LIMIT {
+            if page > 0 {
+                // Stay under the rate limit of the API when used without a token
+                sleep(Duration::from_secs(1)).await;
+            }
+            let mut query_pairs = vec![("limit", "100".to_string())];
+            if let Some(lt) = before_lt {
+                query_pairs.push(("before_lt", format!("{lt}")));
+            }
+            let response = self
+                .api_call::<TonGetAccountEventsResponse>(
+                    format!("accounts/{address}/events"),
+                    query_pairs.iter().map(|(k, v)| (*k, v.as_str())).collect(),
+                )
+                .await
+                .0
+                .to_supported()?;
+            for event in &response.events {
+                for action in event.actions.iter().filter(|a| a.status == "ok") {
+                    let (token, sender, recipient, amount) = if let Some(t) = &action.TonTransfer {
+                        (
+                            self.properties.native_token.clone(),
+                            Some(&t.sender),
+                            Some(&t.recipient),
+                            BigUint::from(t.amount),
+                        )
+                    } else if let Some(t) = &action.JettonTransfer {
+                        let jetton_address = self.parse_token_address(&t.jetton.address);
+                        let token = tokens
+                            .iter()
+                            .find(|token| Some(&token.address) == jetton_address.as_ref());
+                        match (token, BigUint::from_str(&t.amount)) {
+                            (Some(token), Ok(amount)) => (
+                                token.clone(),
+                                t.sender.as_ref(),
+                                t.recipient.as_ref(),
+                                amount,
+                            ),
+                            _ => continue,
+                        }
+                    } else {
+                        continue;
+                    };
+                    let parse = |a: Option<&TonAccountAddress>| {
+                        a.and_then(|a| self.parse_wallet_address(&a.address))
+                    };
+                    let (sender, recipient) = (parse(sender), parse(recipient));
+                    let is_incoming = recipient.as_ref() == Some(&address);
+                    if !is_incoming && sender.as_ref() != Some(&address) {
+                        continue;
+                    }
+                    transfers.push(Transfer {
+                        hash: event.event_id.clone(),
+                        timestamp: event.timestamp,
+                        token,
+                        amount,
+                        is_incoming,
+                        counterparty: if is_incoming { sender } else { recipient },
+                    });
+                }
+            }
+            if response.events.is_empty() || response.next_from == 0 {
+                break;
+            }
+            before_lt = Some(response.next_from);
+            is_truncated = page + 1 == EVENT_PAGES_LIMIT;
+        }
+        SupportOption::SupportedSome(TransferHistory {
+            transfers,
+            skipped_count: 0,
+            is_truncated,
+        })
+    }
     fn parse_token_address(&self, address: &str) -> Option<String> {
         Self::parse_address_to_base64(address, true)
     }
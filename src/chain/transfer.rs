@@ -0,0 +1,36 @@
+use num_bigint::BigUint;
+
+use super::token::Token;
+
+/// Movement of a token in or out of an account
+#[derive(Debug, Clone)]
+pub struct Transfer {
+    pub hash: String,
+    /// Unix timestamp in seconds
+    pub timestamp: i64,
+    pub token: Token,
+    pub amount: BigUint,
+    pub is_incoming: bool,
+    /// Address on the other side of the transfer, when known
+    pub counterparty: Option<String>,
+}
+
+/// Transfers of an account, along with the transactions that had to be left out
+#[derive(Debug, Clone)]
+pub struct TransferHistory {
+    pub transfers: Vec<Transfer>,
+    /// Transactions that could not be read, e.g. pruned by the RPC
+    pub skipped_count: usize,
+    /// Whether older transfers were left out because the history is too long to fetch
+    pub is_truncated: bool,
+}
+
+impl From<Vec<Transfer>> for TransferHistory {
+    fn from(transfers: Vec<Transfer>) -> Self {
+        Self {
+            transfers,
+            skipped_count: 0,
+            is_truncated: false,
+        }
+    }
+}
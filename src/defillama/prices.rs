@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use reqwest::{Client, Url};
+use serde::Deserialize;
+
+static DEFILLAMA_COINS_API: &str = "https://coins.llama.fi";
+
+#[derive(Deserialize, Debug)]
+struct CoinPrice {
+    price: f64,
+}
+
+#[derive(Deserialize, Debug)]
+struct GetHistoricalPricesResponse {
+    coins: HashMap<String, CoinPrice>,
+}
+
+/// Maps a BoP chain ID to the chain identifier used by DefiLlama
+pub fn get_defillama_chain(chain_id: &str) -> Option<&'static str> {
+    Some(match chain_id {
+        "solana" => "solana",
+        "ton" => "ton",
+        "ethereum" => "ethereum",
+        "base" => "base",
+        "bsc" => "bsc",
+        "arbitrum" => "arbitrum",
+        "avalanche" => "avax",
+        "polygon" => "polygon",
+        "zksync" => "era",
+        "cronos" => "cronos",
+        "fantom" => "fantom",
+        "optimism" => "optimism",
+        "linea" => "linea",
+        "mantle" => "mantle",
+        "metis" => "metis",
+        "core" => "core",
+        "scroll" => "scroll",
+        "iotex" => "iotex",
+        "celo" => "celo",
+        "pulsechain" => "pulse",
+        "polygonzkevm" => "polygon_zkevm",
+        "telos" => "telos",
+        _ => return None,
+    })
+}
+
+/// Builds the DefiLlama identifier of a token
+pub fn get_coin_id(chain_id: &str, token_address: &str) -> Option<String> {
+    // TON is tracked through its bridged token on Ethereum
+    if chain_id == "ton" && token_address.starts_with("0x") {
+        return Some(format!("ethereum:{token_address}"));
+    }
+    Some(format!(
+        "{}:{token_address}",
+        get_defillama_chain(chain_id)?
+    ))
+}
+
+/// Fetches the USD price of a coin at the given unix timestamp
+pub async fn get_historical_price(coin_id: &str, timestamp: i64) -> Option<f64> {
+    let url = Url::parse(&format!(
+        "{DEFILLAMA_COINS_API}/prices/historical/{timestamp}/{coin_id}"
+    ))
+    .ok()?;
+    Client::new()
+        .get(url)
+        .send()
+        .await
+        .ok()?
+        .json::<GetHistoricalPricesResponse>()
+        .await
+        .ok()?
+        .coins
+        .remove(coin_id)
+        .map(|c| c.price)
+}
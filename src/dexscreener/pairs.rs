@@ -144,3 +144,33 @@ where
 pub async fn get_pairs(tokens: Vec<&str>, stables: Vec<&str>) -> Option<Vec<Pair>> {
     _get_pairs::<fn()>(tokens, stables, None).await
 }
+
+pub async fn get_token_symbol(token_address: &str) -> Option<String> {
+    let pairs = get_pairs(vec![token_address], vec![]).await?;
+    (!pairs.is_empty()).then(|| pairs[0].base_token.symbol.clone())
+}
+
+/// USD price of the most liquid pair whose base token has the given symbol
+pub async fn search_price(symbol: &str) -> Option<f64> {
+    let url = Url::parse_with_params(
+        "https://api.dexscreener.com/latest/dex/search",
+        &[("q", symbol)],
+    )
+    .ok()?;
+    let task = async |_rpc_index| (get_pairs_request(url.clone()).await, None);
+    let pairs = handle_retry(task).await;
+    let liquidity = |pair: &Pair| {
+        pair.liquidity
+            .as_ref()
+            .and_then(|l| l.usd)
+            .unwrap_or_default()
+    };
+    pairs
+        .iter()
+        .filter(|pair| pair.base_token.symbol.eq_ignore_ascii_case(symbol))
+        .max_by(|pair_a, pair_b| liquidity(pair_a).total_cmp(&liquidity(pair_b)))?
+        .price_usd
+        .as_ref()?
+        .parse()
+        .ok()
+}
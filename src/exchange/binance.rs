@@ -0,0 +1,133 @@
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use reqwest::{Client, Url};
+use serde::Deserialize;
+use sha2::Sha256;
+
+use super::{Exchange, ExchangeOps};
+
+pub struct BinanceExchange {
+    base_url: String,
+    api_key: String,
+    api_secret: String,
+    http_client: Client,
+}
+
+#[derive(Deserialize, Debug)]
+struct AccountBalance {
+    asset: String,
+    free: String,
+    locked: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GetAccountResponse {
+    balances: Vec<AccountBalance>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ErrorResponse {
+    msg: String,
+}
+
+impl From<&Exchange> for BinanceExchange {
+    fn from(value: &Exchange) -> Self {
+        Self {
+            base_url: value.base_url().trim_end_matches('/').to_string(),
+            api_key: value.api_key.clone(),
+            api_secret: value.api_secret.clone(),
+            http_client: Client::new(),
+        }
+    }
+}
+
+impl BinanceExchange {
+    /// Signs the query string with HMAC-SHA256, as required by the `USER_DATA` endpoints
+    fn sign(&self, query: &str) -> Result<String, String> {
+        let mut mac = match Hmac::<Sha256>::new_from_slice(self.api_secret.as_bytes()) {
+            Ok(x) => x,
+            _ => return Err("Invalid API secret".to_string()),
+        };
+        mac.update(query.as_bytes());
+        Ok(mac
+            .finalize()
+            .into_bytes()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect())
+    }
+    async fn signed_get<T: for<'a> Deserialize<'a>>(
+        &self,
+        route: &str,
+        params: &str,
+    ) -> Result<T, String> {
+        let query = format!("{params}&timestamp={}", Utc::now().timestamp_millis());
+        let signature = self.sign(&query)?;
+        let url = match Url::parse(&format!(
+            "{}{route}?{query}&signature={signature}",
+            self.base_url
+        )) {
+            Ok(x) => x,
+            _ => return Err(format!("Invalid API URL {:?}", self.base_url)),
+        };
+        let response = match self
+            .http_client
+            .get(url)
+            .header("X-MBX-APIKEY", &self.api_key)
+            .send()
+            .await
+        {
+            Ok(x) => x,
+            _ => return Err(format!("Could not reach {}", self.base_url)),
+        };
+        if !response.status().is_success() {
+            let status = response.status();
+            return Err(match response.json::<ErrorResponse>().await {
+                Ok(x) => x.msg,
+                _ => status.to_string(),
+            });
+        }
+        match response.json::<T>().await {
+            Ok(x) => Ok(x),
+            _ => Err("Unexpected API response".to_string()),
+        }
+    }
+}
+
+impl ExchangeOps for BinanceExchange {
+    async fn get_balances(&self) -> Result<Vec<(String, f64)>, String> {
+        let account = self
+            .signed_get::<GetAccountResponse>("/api/v3/account", "omitZeroBalances=true")
+            .await?;
+        Ok(account
+            .balances
+            .into_iter()
+            .filter_map(|b| {
+                let amount = b.free.parse::<f64>().ok()? + b.locked.parse::<f64>().ok()?;
+                (amount > 0.0).then_some((b.asset, amount))
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::Client;
+
+    use super::BinanceExchange;
+
+    #[test]
+    fn sign_matches_binance_example() {
+        let exchange = BinanceExchange {
+            base_url: String::new(),
+            api_key: String::new(),
+            api_secret: "NhqPtmdSJYdKjVHjA7PZj4Mge3R5YNiP1e3UZjInClVN65XAbvqqM6A7H5fATj0j"
+                .to_string(),
+            http_client: Client::new(),
+        };
+        assert_eq!(
+            exchange.sign("symbol=LTCBTC&side=BUY&type=LIMIT&timeInForce=GTC&quantity=1&price=0.1&recvWindow=5000&timestamp=1499827319559"),
+            Ok("c8db56825ae71d6d79447849e617115f4a920fa2acdcab2b053c4b2838bd6b71".to_string())
+        );
+    }
+}
@@ -0,0 +1,89 @@
+pub mod binance;
+
+use std::{fmt::Display, str::FromStr};
+
+use binance::BinanceExchange;
+use serde::{Deserialize, Serialize};
+
+pub static EXCHANGE_TYPES: &[ExchangeType; 1] = &[ExchangeType::Binance];
+
+#[derive(Debug, Clone, Serialize, Deserialize, Hash, Eq, PartialEq)]
+pub enum ExchangeType {
+    Binance,
+}
+
+impl ExchangeType {
+    pub fn label(&self) -> String {
+        match self {
+            Self::Binance => "Binance",
+        }
+        .to_string()
+    }
+    pub fn default_base_url(&self) -> &'static str {
+        match self {
+            Self::Binance => "https://api.binance.com",
+        }
+    }
+}
+
+impl Display for ExchangeType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Binance => "binance",
+            }
+        )
+    }
+}
+
+impl FromStr for ExchangeType {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "binance" => Ok(Self::Binance),
+            x => Err(format!("{x:?} is not a valid exchange-type")),
+        }
+    }
+}
+
+/// Exchange account accessed through a read-only API key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Exchange {
+    pub exchange_type: ExchangeType,
+    pub name: String,
+    pub api_key: String,
+    pub api_secret: String,
+    /// Overrides the default API base URL, e.g. to target a testnet or a local mock
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+}
+
+impl Exchange {
+    pub fn base_url(&self) -> &str {
+        self.base_url
+            .as_deref()
+            .unwrap_or(self.exchange_type.default_base_url())
+    }
+}
+
+pub trait ExchangeOps {
+    /// Lists the asset symbols held on the account with their total amount, including
+    /// the amount locked in open orders
+    async fn get_balances(&self) -> Result<Vec<(String, f64)>, String>;
+}
+
+macro_rules! exchange_ops_method {
+    ($self:expr, $method:ident $(, $args:expr)*; await) => {
+        match $self.exchange_type {
+            ExchangeType::Binance => BinanceExchange::from($self).$method($($args),*).await,
+        }
+    };
+}
+
+impl ExchangeOps for Exchange {
+    async fn get_balances(&self) -> Result<Vec<(String, f64)>, String> {
+        exchange_ops_method!(self, get_balances; await)
+    }
+}
@@ -1,20 +1,69 @@
 #![feature(try_trait_v2)]
 
 mod chain;
+mod defillama;
 mod dexscreener;
+mod exchange;
+mod opensea;
 mod repl;
 mod utils;
 
-use repl::Repl;
+use age::secrecy::SecretString;
+use clap::Parser;
+use repl::{OutputFormat, Repl};
+
+static PASSWORD_ENV: &str = "BOP_PASSWORD";
+
+/// Runs the REPL, or a single REPL command when one is given
+#[derive(Parser, Debug)]
+#[command(version = concat!("v", env!("CARGO_PKG_VERSION")), after_help = format!(
+    "The password is prompted with pinentry unless it is set in the {PASSWORD_ENV} \
+    environment variable or read from --password-fd"
+))]
+struct Cli {
+    /// Display balance, account, token and chain as JSON
+    #[arg(long)]
+    json: bool,
+    /// Read the password from the first line of the given file descriptor
+    #[arg(long, value_name = "FD")]
+    password_fd: Option<u32>,
+    /// Command to run instead of starting the REPL, e.g. `balance` or `token add base 0x...`,
+    /// or `run script.bop` to run the commands of a file
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    command: Vec<String>,
+}
+
+fn get_given_password(cli: &Cli) -> Result<Option<SecretString>, String> {
+    if let Some(fd) = cli.password_fd {
+        return match std::fs::read_to_string(format!("/dev/fd/{fd}")) {
+            Ok(x) => Ok(Some(SecretString::from(
+                x.lines().next().unwrap_or_default().to_string(),
+            ))),
+            _ => Err(format!("Could not read password from file descriptor {fd}")),
+        };
+    }
+    Ok(std::env::var(PASSWORD_ENV).ok().map(SecretString::from))
+}
+
+async fn run(cli: Cli) -> Result<(), String> {
+    let mut repl = Repl::default();
+    if cli.json {
+        repl = repl.with_output(OutputFormat::Json);
+    }
+    if let Some(password) = get_given_password(&cli)? {
+        repl = repl.with_password(password);
+    }
+    match cli.command.as_slice() {
+        [] => repl.run().await,
+        [run, path] if run == "run" => repl.run_file(path).await,
+        command => repl.run_command(&command.join(" ")).await,
+    }
+}
 
 #[tokio::main]
 async fn main() {
-    if let Some(arg) = std::env::args().nth(1) {
-        if arg.as_str() == "--version" {
-            return println!("{} v{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
-        }
-    }
-    if let Err(err) = Repl::default().run().await {
+    if let Err(err) = run(Cli::parse()).await {
         eprintln!("Error: {err}");
+        std::process::exit(1);
     }
 }
@@ -0,0 +1,74 @@
+use reqwest::{Client, Url};
+use serde::Deserialize;
+
+static OPENSEA_API: &str = "https://api.opensea.io/api/v2";
+
+#[derive(Deserialize, Debug)]
+struct GetContractResponse {
+    collection: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct CollectionTotalStats {
+    floor_price: Option<f64>,
+    floor_price_symbol: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GetCollectionStatsResponse {
+    total: CollectionTotalStats,
+}
+
+/// Floor price of a collection, in units of `symbol`
+#[derive(Debug, Clone)]
+pub struct FloorPrice {
+    pub price: f64,
+    pub symbol: String,
+}
+
+/// Maps a BoP chain ID to the chain identifier used by OpenSea
+pub fn get_opensea_chain(chain_id: &str) -> Option<&'static str> {
+    Some(match chain_id {
+        "ethereum" => "ethereum",
+        "base" => "base",
+        "arbitrum" => "arbitrum",
+        "optimism" => "optimism",
+        "polygon" => "matic",
+        "avalanche" => "avalanche",
+        "bsc" => "bsc",
+        "zksync" => "zksync",
+        _ => return None,
+    })
+}
+
+async fn get_request<T: for<'de> Deserialize<'de>>(url: &str, api_key: &str) -> Option<T> {
+    Client::new()
+        .get(Url::parse(url).ok()?)
+        .header("x-api-key", api_key)
+        .send()
+        .await
+        .ok()?
+        .json::<T>()
+        .await
+        .ok()
+}
+
+pub async fn get_floor_price(api_key: &str, chain_id: &str, contract: &str) -> Option<FloorPrice> {
+    let chain = get_opensea_chain(chain_id)?;
+    let slug = get_request::<GetContractResponse>(
+        &format!("{OPENSEA_API}/chain/{chain}/contract/{contract}"),
+        api_key,
+    )
+    .await?
+    .collection?;
+    let stats = get_request::<GetCollectionStatsResponse>(
+        &format!("{OPENSEA_API}/collections/{slug}/stats"),
+        api_key,
+    )
+    .await?
+    .total;
+    Some(FloorPrice {
+        price: stats.floor_price?,
+        symbol: stats.floor_price_symbol?,
+    })
+}
@@ -0,0 +1,357 @@
+use itertools::Itertools;
+
+use crate::{
+    chain::ChainOps,
+    utils::{
+        chart::{bar, terminal_width},
+        float::ExtendFloat,
+        table::Table,
+    },
+};
+
+use super::{
+    balance::{ReplBalanceEntry, ReplBalances},
+    Repl,
+};
+
+/// Share of the portfolio above which a group is flagged, in percent
+pub static DEFAULT_CONCENTRATION_THRESHOLD: f64 = 25.0;
+
+pub static ALLOCATION_GROUPS: &[&str; 4] = &["token", "chain", "type", "account"];
+
+/// Drift from the target weight tolerated by default, in percentage points
+pub static DEFAULT_REBALANCE_TOLERANCE: f64 = 5.0;
+
+/// Name of the rebalance target that groups every stablecoin
+static STABLES: &str = "stables";
+
+impl Repl {
+    pub(super) fn concentration_threshold(&self) -> f64 {
+        self.config
+            .concentration_threshold
+            .unwrap_or(DEFAULT_CONCENTRATION_THRESHOLD)
+    }
+    /// Sums the USD value of the balances by token symbol, chain, chain-type or account,
+    /// sorted by value
+    pub(super) fn group_balances(
+        &self,
+        balances: &ReplBalances,
+        group_by: &str,
+    ) -> Vec<(String, f64)> {
+        balances
+            .entries
+            .iter()
+            .into_group_map_by(|b| match group_by {
+                "chain" => b.chain.clone(),
+                "account" => b.account.clone(),
+                "type" => match self.get_chain_by_name(&b.chain) {
+                    Some(chain) => chain.chain_type.label(),
+                    None => b.chain.clone(),
+                },
+                _ => b.token.symbol.clone(),
+            })
+            .into_iter()
+            .map(|(group, entries)| {
+                (
+                    group,
+                    entries.iter().fold(0.0, |sum, b| sum + b.balance_usd),
+                )
+            })
+            .sorted_by(|a, b| b.1.total_cmp(&a.1))
+            .collect()
+    }
+    pub(super) fn display_allocation(&self, balances: &ReplBalances, group_by: &str) {
+        let groups = self.group_balances(balances, group_by);
+        let total = balances.total_usd();
+        let threshold = self.concentration_threshold();
+        let share = |value: f64| {
+            if total == 0.0 {
+                0.0
+            } else {
+                value / total * 100.0
+            }
+        };
+        let group_width = groups
+            .iter()
+            .map(|(g, _)| g.len())
+            .max()
+            .unwrap_or_default();
+        let bar_width = terminal_width()
+            .saturating_sub(group_width + 40)
+            .clamp(10, 50);
+        let mut rows = groups
+            .iter()
+            .map(|(group, value)| {
+                let share = share(*value);
+                Vec::from([
+                    group.clone(),
+                    value.round_to_fixed_string(2),
+                    format!(
+                        "{}%{}",
+                        share.round_to_fixed_string(2),
+                        if share > threshold { " (!)" } else { "" }
+                    ),
+                    bar(share / 100.0, bar_width),
+                ])
+            })
+            .collect::<Vec<_>>();
+        rows.insert(
+            0,
+            Vec::from([
+                match group_by {
+                    "chain" => "Chain",
+                    "type" => "Chain-type",
+                    "account" => "Account",
+                    _ => "Token",
+                }
+                .to_string(),
+                "Balance (USD)".to_string(),
+                "Share".to_string(),
+                String::new(),
+            ]),
+        );
+        let mut t = Table::from(rows);
+        t.title = "Allocation".to_string();
+        println!("{t}");
+        println!("Balance: {} USD", total.round_to_fixed_string(2));
+        let concentrated = groups
+            .iter()
+            .filter(|(_, value)| share(*value) > threshold)
+            .map(|(group, _)| group.as_str())
+            .collect::<Vec<_>>();
+        if !concentrated.is_empty() {
+            println!(
+                "(!) Above the {}% concentration threshold: {}",
+                threshold.round_to_fixed_string(2),
+                concentrated.join(", ")
+            );
+        }
+    }
+    fn find_rebalance_target(&self, asset: &str) -> Option<usize> {
+        self.config
+            .rebalance_targets
+            .iter()
+            .position(|(a, _)| a.eq_ignore_ascii_case(asset))
+    }
+    /// Whether the token of the entry is one of the stablecoins of its chain
+    fn is_stable(&self, entry: &ReplBalanceEntry) -> bool {
+        let chain = match self.get_chain_by_name(&entry.chain) {
+            Some(x) => x,
+            None => return false,
+        };
+        // EVM addresses are hardcoded in lowercase but added tokens are checksummed
+        let address = chain.parse_token_address(&entry.token.address);
+        chain.properties.stables.iter().any(|stable| {
+            stable.address == entry.token.address
+                || (address.is_some() && chain.parse_token_address(&stable.address) == address)
+        })
+    }
+    /// Sums the USD value of the balances by rebalance asset, a token is grouped with the
+    /// other stablecoins unless it has a target weight of its own
+    fn group_balances_by_asset(&self, balances: &ReplBalances) -> Vec<(String, f64)> {
+        balances
+            .entries
+            .iter()
+            .into_group_map_by(|b| match self.find_rebalance_target(&b.token.symbol) {
+                Some(i) => self.config.rebalance_targets[i].0.clone(),
+                None if self.is_stable(b) => STABLES.to_string(),
+                None => b.token.symbol.clone(),
+            })
+            .into_iter()
+            .map(|(asset, entries)| {
+                (
+                    asset,
+                    entries.iter().fold(0.0, |sum, b| sum + b.balance_usd),
+                )
+            })
+            .collect()
+    }
+    fn display_rebalance(&self, balances: &ReplBalances) -> Result<(), String> {
+        let targets = &self.config.rebalance_targets;
+        let targets_sum = targets.iter().fold(0.0, |sum, (_, weight)| sum + weight);
+        if (targets_sum - 100.0).abs() > 0.01 {
+            return Err(format!(
+                "Target weights add up to {}%, expecting 100%",
+                targets_sum.round_to_fixed_string(2)
+            ));
+        }
+        let total = balances.total_usd();
+        if total <= 0.0 {
+            return Err("There is no balance to rebalance".to_string());
+        }
+        let tolerance = self
+            .config
+            .rebalance_tolerance
+            .unwrap_or(DEFAULT_REBALANCE_TOLERANCE);
+        let groups = self.group_balances_by_asset(balances);
+        // Untargeted assets have a target weight of 0%
+        let assets = targets
+            .iter()
+            .map(|(asset, weight)| {
+                let value = groups
+                    .iter()
+                    .find(|(a, _)| a == asset)
+                    .map(|(_, value)| *value)
+                    .unwrap_or_default();
+                (asset.clone(), value, *weight)
+            })
+            .chain(
+                groups
+                    .iter()
+                    .filter(|(asset, _)| !targets.iter().any(|(a, _)| a == asset))
+                    .map(|(asset, value)| (asset.clone(), *value, 0.0)),
+            )
+            .sorted_by(|a, b| b.1.total_cmp(&a.1))
+            .collect::<Vec<_>>();
+        let mut rows = assets
+            .iter()
+            .map(|(asset, value, weight)| {
+                let current = value / total * 100.0;
+                let drift = current - weight;
+                let trade = weight / 100.0 * total - value;
+                Vec::from([
+                    asset.clone(),
+                    value.round_to_fixed_string(2),
+                    format!("{}%", current.round_to_fixed_string(2)),
+                    format!("{}%", weight.round_to_fixed_string(2)),
+                    format!("{}%", drift.round_to_signed_string(2)),
+                    if drift.abs() <= tolerance {
+                        "Hold".to_string()
+                    } else if trade > 0.0 {
+                        format!("Buy {}", trade.round_to_fixed_string(2))
+                    } else {
+                        format!("Sell {}", (-trade).round_to_fixed_string(2))
+                    },
+                ])
+            })
+            .collect::<Vec<_>>();
+        rows.insert(
+            0,
+            Vec::from([
+                "Asset".to_string(),
+                "Balance (USD)".to_string(),
+                "Current".to_string(),
+                "Target".to_string(),
+                "Drift".to_string(),
+                "Trade (USD)".to_string(),
+            ]),
+        );
+        let mut t = Table::from(rows);
+        t.title = "Rebalance".to_string();
+        println!("{t}");
+        println!(
+            "Balance: {} USD\nTolerance: {}%",
+            total.round_to_fixed_string(2),
+            tolerance.round_to_fixed_string(2)
+        );
+        Ok(())
+    }
+    pub(super) async fn handle_rebalance(&mut self, command_parts: &[&str]) -> Result<(), String> {
+        let parse_percent = |s: &str| match s.parse::<f64>() {
+            Ok(x) if (0.0..=100.0).contains(&x) => Ok(x),
+            _ => Err("Invalid percentage".to_string()),
+        };
+        match command_parts.len() {
+            0 => {
+                if self.config.rebalance_targets.is_empty() {
+                    return Err(
+                        "There are no target weights, set them with \"rebalance set\"".to_string(),
+                    );
+                }
+                let balances = self.fetch_balances().await?;
+                self.display_rebalance(&balances)
+            }
+            2 => match command_parts[0] {
+                "rm" => match self.find_rebalance_target(command_parts[1]) {
+                    Some(i) => {
+                        self.config.rebalance_targets.remove(i);
+                        self.store_config_to_data_file()
+                    }
+                    None => Err(format!(
+                        "There is no target weight for {:?}",
+                        command_parts[1]
+                    )),
+                },
+                "tolerance" => {
+                    self.config.rebalance_tolerance = Some(parse_percent(command_parts[1])?);
+                    self.store_config_to_data_file()
+                }
+                _ => Repl::get_unknown_option_expecting_or_err(&["rm", "tolerance"]),
+            },
+            3 => match command_parts[0] {
+                "set" => {
+                    let asset = match command_parts[1] {
+                        x if x.eq_ignore_ascii_case(STABLES) => STABLES,
+                        x => x,
+                    };
+                    let weight = parse_percent(command_parts[2])?;
+                    match self.find_rebalance_target(asset) {
+                        Some(i) => self.config.rebalance_targets[i].1 = weight,
+                        None => self
+                            .config
+                            .rebalance_targets
+                            .push((asset.to_string(), weight)),
+                    };
+                    self.store_config_to_data_file()
+                }
+                _ => Repl::get_unknown_option_expecting_err("set"),
+            },
+            _ => Repl::get_bad_argument_count_err(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use num_bigint::BigUint;
+
+    use crate::{
+        chain::token::Token,
+        repl::{
+            balance::{ReplBalanceEntry, ReplBalances},
+            Repl,
+        },
+    };
+
+    fn get_entry(chain: &str, token: Token, balance_usd: f64) -> ReplBalanceEntry {
+        ReplBalanceEntry {
+            account: "main".to_string(),
+            chain: chain.to_string(),
+            token,
+            balance_native: BigUint::ZERO,
+            underlying: Vec::new(),
+            balance_usd,
+            marker: None,
+            is_debt: false,
+        }
+    }
+
+    #[test]
+    fn checksummed_stablecoins_are_grouped() {
+        let repl = Repl::default();
+        let balances = ReplBalances {
+            entries: Vec::from([
+                get_entry(
+                    "Ethereum",
+                    Token::hardcode("USDT", "0xdAC17F958D2ee523a2206206994597C13D831ec7", 6),
+                    10.0,
+                ),
+                get_entry(
+                    "Ethereum",
+                    Token::hardcode("WETH", "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2", 18),
+                    5.0,
+                ),
+            ]),
+            prices: HashMap::new(),
+            health_factors: Vec::new(),
+        };
+        let mut groups = repl.group_balances_by_asset(&balances);
+        groups.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            groups,
+            [("WETH".to_string(), 5.0), ("stables".to_string(), 10.0)]
+        );
+    }
+}
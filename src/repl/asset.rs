@@ -0,0 +1,191 @@
+use futures::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::{dexscreener, utils::table::Table};
+
+use super::{balance::ReplBalanceEntry, cost::format_price, Repl};
+
+/// Name of the pseudo-chain manual assets are displayed under
+pub static MANUAL_CHAIN: &str = "Manual";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum ManualPrice {
+    /// USD price per unit
+    Fixed(f64),
+    /// Symbol looked up through the price provider
+    Symbol(String),
+}
+
+impl From<&str> for ManualPrice {
+    fn from(value: &str) -> Self {
+        match value.parse::<f64>() {
+            Ok(x) => Self::Fixed(x),
+            _ => Self::Symbol(value.to_string()),
+        }
+    }
+}
+
+impl ManualPrice {
+    fn label(&self) -> String {
+        match self {
+            Self::Fixed(price) => format!("{} USD", format_price(*price)),
+            Self::Symbol(symbol) => format!("Tracked as {symbol}"),
+        }
+    }
+}
+
+/// Off-chain holding such as cash, exchange balances or illiquid tokens
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ManualAsset {
+    pub name: String,
+    pub amount: f64,
+    pub price: ManualPrice,
+}
+
+impl Repl {
+    fn find_manual_asset(&self, name: &str) -> Option<usize> {
+        self.config
+            .manual_assets
+            .iter()
+            .position(|a| a.name.eq_ignore_ascii_case(name))
+    }
+    /// Balance entries of the manual assets, along with the names of those whose price
+    /// could not be found
+    pub(super) async fn get_manual_balances(&self) -> (Vec<ReplBalanceEntry>, Vec<String>) {
+        let assets = &self.config.manual_assets;
+        self.spinner.set_total(assets.len());
+        self.spinner.start(Some("Fetching manual asset prices..."));
+        let prices = stream::iter(assets.iter())
+            .map(async |asset| {
+                let price = match &asset.price {
+                    ManualPrice::Fixed(price) => Some(*price),
+                    ManualPrice::Symbol(symbol) => dexscreener::pairs::search_price(symbol).await,
+                };
+                self.spinner.inc_progress();
+                price
+            })
+            .buffered(5)
+            .collect::<Vec<_>>()
+            .await;
+        self.spinner.stop();
+        let unpriced = assets
+            .iter()
+            .zip(&prices)
+            .filter(|(_, price)| price.is_none())
+            .map(|(asset, _)| asset.name.clone())
+            .collect();
+        let entries = assets
+            .iter()
+            .zip(prices)
+            .map(|(asset, price)| {
+                ReplBalanceEntry::off_chain(
+                    MANUAL_CHAIN,
+                    "-",
+                    &asset.name,
+                    asset.amount,
+                    price.unwrap_or_default(),
+                )
+            })
+            .collect();
+        (entries, unpriced)
+    }
+    fn parse_manual_amount(amount: &str) -> Result<f64, String> {
+        match amount.parse::<f64>() {
+            Ok(x) if x >= 0.0 => Ok(x),
+            _ => Err("Invalid amount".to_string()),
+        }
+    }
+    pub(super) fn handle_asset(&mut self, command_parts: &[&str]) -> Result<(), String> {
+        match command_parts.len() {
+            0 => {
+                if self.config.manual_assets.is_empty() {
+                    println!("You have no manual assets");
+                    return Ok(());
+                }
+                let mut rows = self
+                    .config
+                    .manual_assets
+                    .iter()
+                    .map(|a| Vec::from([a.name.clone(), a.amount.to_string(), a.price.label()]))
+                    .collect::<Vec<_>>();
+                rows.insert(
+                    0,
+                    Vec::from([
+                        "Name".to_string(),
+                        "Amount".to_string(),
+                        "Price".to_string(),
+                    ]),
+                );
+                let mut t = Table::from(rows);
+                t.title = "Manual assets".to_string();
+                println!("{t}");
+                Ok(())
+            }
+            2 => match command_parts[0] {
+                "rm" => match self.find_manual_asset(command_parts[1]) {
+                    Some(i) => {
+                        self.config.manual_assets.remove(i);
+                        self.store_config_to_data_file()
+                    }
+                    None => Err(format!(
+                        "There is no manual asset named {:?}",
+                        command_parts[1]
+                    )),
+                },
+                _ => Repl::get_unknown_option_expecting_err("rm"),
+            },
+            3 | 4 => match command_parts[0] {
+                "add" => {
+                    if command_parts.len() != 4 {
+                        return Repl::get_bad_argument_count_err();
+                    }
+                    let name = command_parts[1];
+                    if self.find_manual_asset(name).is_some() {
+                        return Err(format!(
+                            "There is already a manual asset named {name:?}, use \"asset edit\""
+                        ));
+                    }
+                    let amount = Self::parse_manual_amount(command_parts[2])?;
+                    let price = ManualPrice::from(command_parts[3]);
+                    if let ManualPrice::Fixed(x) = price {
+                        if x < 0.0 {
+                            return Err("Invalid price".to_string());
+                        }
+                    }
+                    self.config.manual_assets.push(ManualAsset {
+                        name: name.to_string(),
+                        amount,
+                        price,
+                    });
+                    self.store_config_to_data_file()
+                }
+                "edit" => {
+                    let i = match self.find_manual_asset(command_parts[1]) {
+                        Some(x) => x,
+                        None => {
+                            return Err(format!(
+                                "There is no manual asset named {:?}",
+                                command_parts[1]
+                            ))
+                        }
+                    };
+                    let amount = Self::parse_manual_amount(command_parts[2])?;
+                    let price = command_parts.get(3).map(|p| ManualPrice::from(*p));
+                    if let Some(ManualPrice::Fixed(x)) = price {
+                        if x < 0.0 {
+                            return Err("Invalid price".to_string());
+                        }
+                    }
+                    let asset = &mut self.config.manual_assets[i];
+                    asset.amount = amount;
+                    if let Some(price) = price {
+                        asset.price = price;
+                    }
+                    self.store_config_to_data_file()
+                }
+                _ => Repl::get_unknown_option_expecting_or_err(&["add", "edit"]),
+            },
+            _ => Repl::get_bad_argument_count_err(),
+        }
+    }
+}
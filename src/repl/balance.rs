@@ -0,0 +1,776 @@
+use std::collections::HashMap;
+
+use futures::{stream, StreamExt};
+use itertools::Itertools;
+use num_bigint::BigUint;
+use serde::{Serialize, Serializer};
+
+use crate::{
+    chain::{chain_type::ChainType, token::Token, ChainOps},
+    dexscreener,
+    utils::{
+        float::ExtendFloat,
+        retry::{handle_limited_retry_indexed, handle_retry_indexed},
+        table::{CsvRenderer, HtmlRenderer, MarkdownRenderer, Table, TableRenderer},
+    },
+};
+
+use super::{allocation::ALLOCATION_GROUPS, cost::format_price, history::Snapshot, Repl};
+
+fn serialize_biguint<S: Serializer>(value: &BigUint, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+fn serialize_underlying<S: Serializer>(
+    value: &[(Token, BigUint)],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(
+        value
+            .iter()
+            .map(|(token, amount)| (token, amount.to_string())),
+    )
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplBalanceEntry {
+    pub chain: String,
+    pub account: String,
+    pub token: Token,
+    #[serde(serialize_with = "serialize_biguint")]
+    pub balance_native: BigUint,
+    /// Tokens backing the balance, for tokens that are valued through them
+    #[serde(serialize_with = "serialize_underlying")]
+    pub underlying: Vec<(Token, BigUint)>,
+    pub balance_usd: f64,
+    /// Short tag displayed next to the token, e.g. "LP" for liquidity positions
+    pub marker: Option<&'static str>,
+    /// Borrowed balances are subtracted from the portfolio
+    pub is_debt: bool,
+}
+
+/// Decimals used to store off-chain amounts as a token balance
+static OFF_CHAIN_DECIMALS: usize = 9;
+/// Attempts at reading the liquidity or lending positions of an account, as each attempt
+/// makes many calls
+static MAX_POSITION_ATTEMPTS: usize = 6;
+
+impl ReplBalanceEntry {
+    /// Entry of a pseudo-chain such as manual assets or exchanges, where the token is
+    /// identified by its symbol
+    pub fn off_chain(chain: &str, account: &str, symbol: &str, amount: f64, price: f64) -> Self {
+        let balance_native = (amount * 10f64.powi(OFF_CHAIN_DECIMALS as i32)).round() as u128;
+        Self {
+            chain: chain.to_string(),
+            account: account.to_string(),
+            token: Token::hardcode(symbol, symbol, OFF_CHAIN_DECIMALS),
+            balance_native: BigUint::from(balance_native),
+            underlying: Vec::new(),
+            balance_usd: price * amount,
+            marker: None,
+            is_debt: false,
+        }
+    }
+    pub fn label(&self) -> String {
+        match self.marker {
+            Some(marker) => format!("{} [{marker}]", self.token.symbol),
+            None => self.token.symbol.clone(),
+        }
+    }
+}
+
+/// Result of querying the balances of every tracked account
+#[derive(Serialize)]
+pub struct ReplBalances {
+    /// Entries worth at least a cent, sorted by USD value
+    pub entries: Vec<ReplBalanceEntry>,
+    /// Map of token address to USD price
+    pub prices: HashMap<String, f64>,
+    /// Vec of account, chain name and health factor of lending positions
+    pub health_factors: Vec<(String, String, f64)>,
+}
+
+impl ReplBalances {
+    pub fn total_usd(&self) -> f64 {
+        self.entries.iter().fold(0.0, |sum, b| sum + b.balance_usd)
+    }
+    pub fn get_price(&self, token: &Token) -> Option<f64> {
+        self.prices.get(&token.address).copied()
+    }
+}
+
+impl Repl {
+    pub(super) async fn fetch_balances(&self) -> Result<ReplBalances, String> {
+        // TODO: Remove the partition part by making use of the `SupportOption`
+        // and stream everything together
+
+        // Partition between the accounts that support `get_holdings_balance` and those
+        // that do not
+        let (accounts_supported, accounts_not_supported): (Vec<_>, Vec<_>) = self
+            .config
+            .accounts
+            .iter()
+            .flat_map(|(chain_type, address, alias)| {
+                self.enabled_chains_of_type(chain_type)
+                    .map(move |chain| (chain, address, alias))
+            })
+            .partition(|(chain, _, _)| chain.chain_type == ChainType::Ton);
+
+        let accounts_not_supported = accounts_not_supported
+            .iter()
+            .flat_map(|(chain, address, alias)| {
+                self.tokens_of_chain(chain)
+                    .map(move |(_, token)| (chain, token.clone(), address, alias))
+            })
+            .collect::<Vec<_>>();
+
+        let accounts_natives = self
+            .enabled_chains()
+            .flat_map(|chain| {
+                self.accounts_of_type(&chain.chain_type)
+                    .map(move |(_, address, alias)| (chain, address, alias))
+            })
+            .collect::<Vec<_>>();
+
+        let accounts_positions = self
+            .enabled_chains()
+            .filter(|chain| !chain.properties.position_managers.is_empty())
+            .flat_map(|chain| {
+                self.accounts_of_type(&chain.chain_type)
+                    .map(move |(_, address, alias)| (chain, address, alias))
+            })
+            .collect::<Vec<_>>();
+
+        let accounts_lending = self
+            .enabled_chains()
+            .filter(|chain| !chain.properties.lending_pools.is_empty())
+            .flat_map(|chain| {
+                self.accounts_of_type(&chain.chain_type)
+                    .map(move |(_, address, alias)| (chain, address, alias))
+            })
+            .collect::<Vec<_>>();
+
+        let total_balances = accounts_supported.len()
+            + accounts_not_supported.len()
+            + accounts_natives.len()
+            + accounts_positions.len()
+            + accounts_lending.len();
+
+        self.spinner.set_total(total_balances);
+        self.spinner.start(Some("Querying balances..."));
+
+        let mut balances: Vec<ReplBalanceEntry> = Vec::new();
+
+        let results_natives = stream::iter(accounts_natives.iter().enumerate())
+            .map(async |(i, (chain, address, _))| {
+                let task = |rpc_index| chain.get_native_token_balance(address, rpc_index);
+                let result = handle_retry_indexed(i, task).await;
+                self.spinner.inc_progress();
+                self.spinner
+                    .set_desc(Some(format!("({})", chain.properties.name)));
+                result
+            })
+            .buffer_unordered(20)
+            .collect::<Vec<_>>()
+            .await;
+
+        let results_not_supported = stream::iter(accounts_not_supported.iter().enumerate())
+            .map(async |(i, (chain, token, address, _))| {
+                let task = |rpc_index| chain.get_token_balance(token, address, rpc_index);
+                let result = handle_retry_indexed(i, task).await;
+                self.spinner.inc_progress();
+                self.spinner
+                    .set_desc(Some(format!("({})", chain.properties.name)));
+                result
+            })
+            .buffer_unordered(20)
+            .collect::<Vec<_>>()
+            .await;
+
+        let results_supported = stream::iter(accounts_supported.iter().enumerate())
+            .map(async |(i, (chain, address, _))| {
+                let task = async |rpc_index| {
+                    (
+                        chain
+                            .get_holdings_balance(address, rpc_index)
+                            .await
+                            .to_result()
+                            .unwrap(),
+                        None,
+                    )
+                };
+                let result = handle_retry_indexed(i, task).await;
+                self.spinner.inc_progress();
+                self.spinner
+                    .set_desc(Some(format!("({})", chain.properties.name)));
+                result
+            })
+            .buffer_unordered(20)
+            .collect::<Vec<_>>()
+            .await;
+
+        let results_positions = stream::iter(accounts_positions.iter().enumerate())
+            .map(async |(i, (chain, address, _))| {
+                let task = async |rpc_index| {
+                    (
+                        chain
+                            .get_liquidity_positions(address, rpc_index)
+                            .await
+                            .to_result()
+                            .unwrap(),
+                        None,
+                    )
+                };
+                let (i, result) =
+                    handle_limited_retry_indexed(i, MAX_POSITION_ATTEMPTS, task).await;
+                let result = (
+                    i,
+                    result.unwrap_or_else(|| {
+                        eprintln!(
+                            "Could not fetch the liquidity positions of {address} on {}",
+                            chain.properties.name
+                        );
+                        Vec::new()
+                    }),
+                );
+                self.spinner.inc_progress();
+                self.spinner
+                    .set_desc(Some(format!("({})", chain.properties.name)));
+                result
+            })
+            .buffer_unordered(20)
+            .collect::<Vec<_>>()
+            .await;
+
+        let results_lending = stream::iter(accounts_lending.iter().enumerate())
+            .map(async |(i, (chain, address, _))| {
+                let task = async |rpc_index| {
+                    (
+                        chain
+                            .get_lending_positions(address, rpc_index)
+                            .await
+                            .to_result()
+                            .unwrap(),
+                        None,
+                    )
+                };
+                let (i, result) =
+                    handle_limited_retry_indexed(i, MAX_POSITION_ATTEMPTS, task).await;
+                let result = (
+                    i,
+                    result.unwrap_or_else(|| {
+                        eprintln!(
+                            "Could not fetch the lending positions of {address} on {}",
+                            chain.properties.name
+                        );
+                        Vec::new()
+                    }),
+                );
+                self.spinner.inc_progress();
+                self.spinner
+                    .set_desc(Some(format!("({})", chain.properties.name)));
+                result
+            })
+            .buffer_unordered(20)
+            .collect::<Vec<_>>()
+            .await;
+
+        self.spinner.stop();
+
+        balances.extend(results_natives.iter().filter_map(|(i, balance)| {
+            let (chain, address, alias) = &accounts_natives[*i];
+            let account_label = Repl::format_account(address, alias);
+            (*balance != BigUint::ZERO).then(|| ReplBalanceEntry {
+                account: account_label.clone(),
+                chain: chain.properties.name.clone(),
+                token: chain.properties.native_token.clone(),
+                balance_native: balance.clone(),
+                underlying: Vec::new(),
+                balance_usd: 0.0,
+                marker: None,
+                is_debt: false,
+            })
+        }));
+
+        balances.extend(results_not_supported.iter().filter_map(|(i, balance)| {
+            let (chain, token, address, alias) = &accounts_not_supported[*i];
+            let account_label = Repl::format_account(address, alias);
+            (*balance != BigUint::ZERO).then(|| ReplBalanceEntry {
+                account: account_label.clone(),
+                chain: chain.properties.name.clone(),
+                token: token.clone(),
+                balance_native: balance.clone(),
+                underlying: Vec::new(),
+                balance_usd: 0.0,
+                marker: None,
+                is_debt: false,
+            })
+        }));
+
+        balances.extend(results_supported.iter().flat_map(|(i, account_holdings)| {
+            let (chain, address, alias) = &accounts_supported[*i];
+            let account_label = Repl::format_account(address, alias);
+            let mut tokens_of_chain = self.tokens_of_chain(chain);
+            account_holdings
+                .iter()
+                .filter_map(move |(token_address, balance)| {
+                    let (_, token) = tokens_of_chain.find(|(_, t)| t.address == *token_address)?;
+                    (*balance != BigUint::ZERO).then(|| ReplBalanceEntry {
+                        account: account_label.clone(),
+                        chain: chain.properties.name.clone(),
+                        token: token.clone(),
+                        balance_native: balance.clone(),
+                        underlying: Vec::new(),
+                        balance_usd: 0.0,
+                        marker: None,
+                        is_debt: false,
+                    })
+                })
+        }));
+
+        balances.extend(results_positions.iter().flat_map(|(i, positions)| {
+            let (chain, address, alias) = &accounts_positions[*i];
+            let account_label = Repl::format_account(address, alias);
+            positions.iter().map(move |position| {
+                // Uncollected fees are listed apart from the liquidity itself
+                let fees = [
+                    (&position.token0, &position.fees0),
+                    (&position.token1, &position.fees1),
+                ]
+                .into_iter()
+                .filter(|(_, fees)| **fees != BigUint::ZERO)
+                .map(|(token, fees)| {
+                    let mut fee_token = token.clone();
+                    fee_token.symbol = format!("{} (fees)", token.symbol);
+                    (fee_token, fees.clone())
+                });
+                ReplBalanceEntry {
+                    account: account_label.clone(),
+                    chain: chain.properties.name.clone(),
+                    token: Token::hardcode(&position.label(), &position.manager, 0),
+                    balance_native: BigUint::from(1u8),
+                    underlying: Vec::from([
+                        (position.token0.clone(), position.amount0.clone()),
+                        (position.token1.clone(), position.amount1.clone()),
+                    ])
+                    .into_iter()
+                    .chain(fees)
+                    .collect(),
+                    balance_usd: 0.0,
+                    marker: Some(if position.in_range {
+                        "LP"
+                    } else {
+                        "LP, out of range"
+                    }),
+                    is_debt: false,
+                }
+            })
+        }));
+
+        let mut health_factors = Vec::new();
+        for (i, positions) in &results_lending {
+            let (chain, address, alias) = &accounts_lending[*i];
+            let account_label = Repl::format_account(address, alias);
+            for position in positions {
+                // The collateral is listed by its underlying token, so tracked aTokens of
+                // the same account would count it twice
+                balances.retain(|entry| {
+                    entry.chain != chain.properties.name
+                        || entry.account != account_label
+                        || !position
+                            .collateral_tokens
+                            .iter()
+                            .any(|x| x.eq_ignore_ascii_case(&entry.token.address))
+                });
+                if let Some(health_factor) = position.health_factor {
+                    health_factors.push((
+                        account_label.clone(),
+                        chain.properties.name.clone(),
+                        health_factor,
+                    ));
+                }
+                let collateral = position
+                    .collateral
+                    .iter()
+                    .map(|(token, amount)| (token, amount, false));
+                let debt = position
+                    .debt
+                    .iter()
+                    .map(|(token, amount)| (token, amount, true));
+                balances.extend(collateral.chain(debt).map(|(token, amount, is_debt)| {
+                    ReplBalanceEntry {
+                        account: account_label.clone(),
+                        chain: chain.properties.name.clone(),
+                        token: token.clone(),
+                        balance_native: amount.clone(),
+                        underlying: Vec::new(),
+                        balance_usd: 0.0,
+                        marker: Some(if is_debt { "Debt" } else { "Collateral" }),
+                        is_debt,
+                    }
+                }));
+            }
+        }
+
+        let backed_balances = balances
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| !b.token.kind.is_standard())
+            .map(|(i, b)| (i, self.find_chain_by_name(&b.chain)))
+            .collect::<Vec<_>>();
+
+        self.spinner.set_total(backed_balances.len());
+        self.spinner.start(Some("Querying underlying assets..."));
+
+        let results_underlying = stream::iter(backed_balances.iter())
+            .map(async |(i, chain)| {
+                let balance = &balances[*i];
+                let task = |rpc_index| {
+                    chain.get_underlying_balances(
+                        &balance.token,
+                        &balance.balance_native,
+                        rpc_index,
+                    )
+                };
+                let result = handle_retry_indexed(*i, task).await;
+                self.spinner.inc_progress();
+                result
+            })
+            .buffer_unordered(20)
+            .collect::<Vec<_>>()
+            .await;
+
+        self.spinner.stop();
+
+        for (i, underlying) in results_underlying {
+            balances[i].underlying = underlying;
+        }
+
+        let tokens_to_fetch_price = balances
+            .iter()
+            .flat_map(|b| {
+                if b.underlying.is_empty() {
+                    Vec::from([&b.token])
+                } else {
+                    b.underlying.iter().map(|(t, _)| t).collect()
+                }
+            })
+            .map(|t| t.address.as_str())
+            .unique()
+            .collect::<Vec<_>>();
+
+        self.spinner.set_total(tokens_to_fetch_price.len());
+        self.spinner.start(Some("Fetching token prices..."));
+
+        let stables = self
+            .chains
+            .iter()
+            .flat_map(|c| &c.properties.stables)
+            .map(|stable| stable.address.as_str())
+            .collect::<Vec<_>>();
+
+        let pairs = match dexscreener::pairs::get_pairs_with_progress(
+            tokens_to_fetch_price,
+            stables,
+            Some(|| {
+                self.spinner.inc_progress();
+            }),
+        )
+        .await
+        {
+            Some(x) => x,
+            None => return Err("Could not fetch tokens price".to_string()),
+        }
+        .iter()
+        .filter_map(|p| {
+            let price: f64 = p.price_usd.clone()?.parse().ok()?;
+            Some((p.base_token.address.clone(), price))
+        })
+        .collect::<Vec<_>>();
+
+        self.spinner.stop();
+
+        let mut prices = HashMap::new();
+        for (address, price) in pairs {
+            // The first pair is the most liquid one
+            prices.entry(address).or_insert(price);
+        }
+        let get_price = |token: &Token| prices.get(&token.address).copied();
+        for balance in &mut balances {
+            let value = if balance.underlying.is_empty() {
+                get_price(&balance.token)
+                    .map(|price| price * balance.token.format(&balance.balance_native))
+                    .unwrap_or_default()
+            } else {
+                balance
+                    .underlying
+                    .iter()
+                    .filter_map(|(token, amount)| Some(get_price(token)? * token.format(amount)))
+                    .sum()
+            };
+            balance.balance_usd = if balance.is_debt { -value } else { value };
+        }
+        if !self.config.manual_assets.is_empty() {
+            let (manual_balances, unpriced) = self.get_manual_balances().await;
+            balances.extend(manual_balances);
+            if !unpriced.is_empty() {
+                eprintln!(
+                    "Could not find the price of manual assets: {}",
+                    unpriced.join(", ")
+                );
+            }
+        }
+        if !self.config.exchanges.is_empty() {
+            balances.extend(self.get_exchange_balances().await);
+        }
+        if self.config.nfts_in_balance {
+            let nft_holdings = self.get_nft_holdings().await;
+            balances.extend(nft_holdings.into_iter().map(|h| ReplBalanceEntry {
+                balance_usd: h.value_usd().unwrap_or_default(),
+                account: h.account,
+                chain: h.chain,
+                token: Token::hardcode(&h.collection.name, &h.collection.address, 0),
+                balance_native: h.count,
+                underlying: Vec::new(),
+                marker: Some("NFT"),
+                is_debt: false,
+            }));
+        }
+        balances.sort_by(|a, b| b.balance_usd.total_cmp(&a.balance_usd));
+        balances.retain(|balance| balance.balance_usd.abs() >= 0.01);
+        Ok(ReplBalances {
+            entries: balances,
+            prices,
+            health_factors,
+        })
+    }
+    /// Table of the balance entries along with the total unrealized PnL of those with cost
+    /// basis lots
+    fn get_balance_table(&self, balances: &ReplBalances) -> Result<(Table, Option<f64>), String> {
+        let lots = self.get_remaining_lots()?;
+        let mut total_pnl = None;
+        let mut rows = balances
+            .entries
+            .iter()
+            .flat_map(|balance| {
+                let pnl = self.get_unrealized_pnl(&lots, balance);
+                if let Some(pnl) = &pnl {
+                    total_pnl = Some(total_pnl.unwrap_or(0.0) + pnl.pnl);
+                }
+                let row = Vec::from([
+                    balance.account.clone(),
+                    balance.chain.clone(),
+                    balance.label(),
+                    balance.token.format(&balance.balance_native).to_string(),
+                    balance.balance_usd.round_to_fixed_string(2),
+                    pnl.as_ref()
+                        .map(|pnl| format_price(pnl.avg_cost))
+                        .unwrap_or("-".to_string()),
+                    pnl.as_ref()
+                        .map(|pnl| pnl.pnl.round_to_signed_string(2))
+                        .unwrap_or("-".to_string()),
+                    pnl.as_ref()
+                        .map(|pnl| format!("{}%", pnl.pnl_percent.round_to_signed_string(2)))
+                        .unwrap_or("-".to_string()),
+                ]);
+                // Breakdown of the tokens backing the balance
+                let underlying_rows = balance.underlying.iter().map(|(token, amount)| {
+                    Vec::from([
+                        String::new(),
+                        String::new(),
+                        format!("↳ {}", token.symbol),
+                        token.format(amount).to_string(),
+                        balances
+                            .get_price(token)
+                            .map(|price| (price * token.format(amount)).round_to_fixed_string(2))
+                            .unwrap_or("-".to_string()),
+                        String::new(),
+                        String::new(),
+                        String::new(),
+                    ])
+                });
+                std::iter::once(row).chain(underlying_rows)
+            })
+            .collect::<Vec<_>>();
+        rows.insert(
+            0,
+            Vec::from([
+                "Account".to_string(),
+                "Chain".to_string(),
+                "Token".to_string(),
+                "Balance".to_string(),
+                "Balance (USD)".to_string(),
+                "Avg cost (USD)".to_string(),
+                "PnL (USD)".to_string(),
+                "PnL".to_string(),
+            ]),
+        );
+        let mut t = Table::from(rows);
+        t.title = "Balances".to_string();
+        Ok((t, total_pnl))
+    }
+    /// Table of the USD value of the balances grouped by chain or account
+    fn get_breakdown_table(&self, balances: &ReplBalances, group_by: &str) -> Table {
+        let total = balances.total_usd();
+        let mut rows = self
+            .group_balances(balances, group_by)
+            .into_iter()
+            .map(|(group, value)| {
+                let share = if total == 0.0 {
+                    0.0
+                } else {
+                    value / total * 100.0
+                };
+                Vec::from([
+                    group,
+                    value.round_to_fixed_string(2),
+                    format!("{}%", share.round_to_fixed_string(2)),
+                ])
+            })
+            .collect::<Vec<_>>();
+        rows.insert(
+            0,
+            Vec::from([
+                format!("{}{}", group_by[..1].to_uppercase(), &group_by[1..]),
+                "Balance (USD)".to_string(),
+                "Share".to_string(),
+            ]),
+        );
+        let mut t = Table::from(rows);
+        t.title = format!("Balance by {group_by}");
+        t
+    }
+    fn export_balances(
+        &self,
+        balances: &ReplBalances,
+        renderer: &dyn TableRenderer,
+        path: &str,
+    ) -> Result<(), String> {
+        let (balance_table, _) = self.get_balance_table(balances)?;
+        let tables = Vec::from([
+            balance_table,
+            self.get_breakdown_table(balances, "chain"),
+            self.get_breakdown_table(balances, "account"),
+        ]);
+        if std::fs::write(path, renderer.render_document(&tables)).is_err() {
+            return Err(format!("Could not write {path}"));
+        }
+        println!("Balance exported to {path}");
+        Ok(())
+    }
+    pub(super) fn display_balances(&self, balances: &ReplBalances) -> Result<(), String> {
+        let (t, total_pnl) = self.get_balance_table(balances)?;
+        println!("{t}");
+        println!(
+            "Holdings: {}\nBalance: {} USD",
+            balances.entries.len(),
+            balances.total_usd().round_to_fixed_string(2),
+        );
+        if balances.entries.iter().any(|b| b.is_debt) {
+            let (debt, assets): (Vec<&ReplBalanceEntry>, Vec<&ReplBalanceEntry>) =
+                balances.entries.iter().partition(|b| b.is_debt);
+            println!(
+                "Assets: {} USD\nDebt: {} USD",
+                assets
+                    .iter()
+                    .fold(0.0, |sum, b| sum + b.balance_usd)
+                    .round_to_fixed_string(2),
+                debt.iter()
+                    .fold(0.0, |sum, b| sum - b.balance_usd)
+                    .round_to_fixed_string(2),
+            );
+        }
+        if let Some(total_pnl) = total_pnl {
+            println!(
+                "Unrealized PnL: {} USD",
+                total_pnl.round_to_signed_string(2)
+            );
+        }
+        for (account, chain, health_factor) in &balances.health_factors {
+            println!(
+                "Health factor ({account} on {chain}): {}",
+                health_factor.round_to_fixed_string(2)
+            );
+        }
+        Ok(())
+    }
+    pub(super) async fn handle_balance(&mut self, command_parts: &[&str]) -> Result<(), String> {
+        match command_parts.len() {
+            0 => {
+                let balances = self.fetch_balances().await?;
+                if self.is_json_output() {
+                    Self::print_json(&balances)?;
+                } else {
+                    self.display_balances(&balances)?;
+                }
+                self.record_snapshot(&balances)
+            }
+            1 | 2 => match command_parts[0] {
+                "diff" => {
+                    let history = self.read_history()?;
+                    let index = match command_parts.get(1) {
+                        Some(id) => Self::parse_snapshot_id(&history, id)?,
+                        None => match history.len().checked_sub(1) {
+                            Some(x) => x,
+                            None => return Err("There are no snapshots to compare to".to_string()),
+                        },
+                    };
+                    let balances = self.fetch_balances().await?;
+                    Self::display_diff(&history[index], &Snapshot::from(&balances));
+                    Ok(())
+                }
+                "alloc" => {
+                    let group_by = command_parts.get(1).copied().unwrap_or("token");
+                    if !ALLOCATION_GROUPS.contains(&group_by) {
+                        return Repl::get_unknown_option_expecting_or_err(ALLOCATION_GROUPS);
+                    }
+                    let balances = self.fetch_balances().await?;
+                    self.display_allocation(&balances, group_by);
+                    Ok(())
+                }
+                "watch" => self.watch_balances(command_parts.get(1).copied()).await,
+                "threshold" => {
+                    let threshold = match command_parts.get(1) {
+                        Some(x) => x,
+                        None => {
+                            println!(
+                                "Concentration threshold: {}%",
+                                self.concentration_threshold().round_to_fixed_string(2)
+                            );
+                            return Ok(());
+                        }
+                    };
+                    self.config.concentration_threshold = match threshold.parse::<f64>() {
+                        Ok(x) if (0.0..=100.0).contains(&x) => Some(x),
+                        _ => return Err("Invalid percentage".to_string()),
+                    };
+                    self.store_config_to_data_file()
+                }
+                _ => Repl::get_unknown_option_expecting_or_err(&[
+                    "diff",
+                    "alloc",
+                    "threshold",
+                    "watch",
+                ]),
+            },
+            3 => match command_parts[0] {
+                "export" => {
+                    let renderer: &dyn TableRenderer = match command_parts[1] {
+                        "csv" => &CsvRenderer,
+                        "md" => &MarkdownRenderer,
+                        "html" => &HtmlRenderer,
+                        _ => {
+                            return Repl::get_unknown_option_expecting_or_err(&[
+                                "csv", "md", "html",
+                            ])
+                        }
+                    };
+                    let balances = self.fetch_balances().await?;
+                    self.export_balances(&balances, renderer, command_parts[2])
+                }
+                _ => Repl::get_unknown_option_expecting_err("export"),
+            },
+            _ => Repl::get_bad_argument_count_err(),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use super::{
+    data_file::{remove_file, write_file, COMMAND_HISTORY_FILE},
+    Repl,
+};
+
+/// Number of commands kept, matching the default size of the editor history
+static MAX_COMMAND_HISTORY: usize = 100;
+/// Commands taking API keys or RPC urls, which often embed a key, as arguments
+static SECRET_COMMANDS: [&str; 3] = ["exchange add", "nft key", "chain set"];
+
+impl Repl {
+    /// Whether the command takes a secret, so that it is left out of the history
+    pub(super) fn has_secret(command: &str) -> bool {
+        let words = command.split_whitespace().collect::<Vec<_>>();
+        SECRET_COMMANDS.iter().any(|secret_command| {
+            let secret_words = secret_command.split(' ').collect::<Vec<_>>();
+            words.starts_with(&secret_words)
+        })
+    }
+    pub(super) fn read_command_history(&self) -> Result<Vec<String>, String> {
+        if self.config.command_history_disabled {
+            return Ok(Vec::new());
+        }
+        // The REPL may have been killed while writing the history, which is not worth
+        // failing the startup for
+        match self.read_encrypted_file(COMMAND_HISTORY_FILE) {
+            Ok(x) => Ok(x),
+            Err(err) => {
+                eprintln!("{err}, the command history is reset");
+                Ok(Vec::new())
+            }
+        }
+    }
+    /// Waits for the command history to be written, if it is being written
+    pub(super) fn wait_command_history_stored(&mut self) {
+        if let Some(task) = self.command_history_task.take() {
+            let _ = task.join();
+        }
+    }
+    /// Writes the command history on a separate thread, as encrypting it takes a while
+    pub(super) fn store_command_history(&mut self) {
+        self.wait_command_history_stored();
+        if self.config.command_history_disabled {
+            return;
+        }
+        let skip = self
+            .command_history
+            .len()
+            .saturating_sub(MAX_COMMAND_HISTORY);
+        let contents = serde_json::to_vec(&self.command_history[skip..]).unwrap();
+        let secret = self.secret.clone();
+        self.command_history_task = Some(std::thread::spawn(move || {
+            let result = Repl::encrypt_with(secret.as_ref(), contents)
+                .and_then(|x| write_file(COMMAND_HISTORY_FILE, &x));
+            if let Err(err) = result {
+                eprintln!("{err}");
+            }
+        }));
+    }
+    pub(super) fn clear_command_history(&mut self) -> Result<(), String> {
+        self.wait_command_history_stored();
+        self.command_history.clear();
+        remove_file(COMMAND_HISTORY_FILE)
+    }
+    pub(super) fn toggle_command_history(&mut self) -> Result<(), String> {
+        self.config.command_history_disabled = !self.config.command_history_disabled;
+        self.store_config_to_data_file()?;
+        if self.config.command_history_disabled {
+            self.wait_command_history_stored();
+            remove_file(COMMAND_HISTORY_FILE)?;
+            println!("Command history disabled");
+        } else {
+            self.store_command_history();
+            println!("Command history enabled");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    chain::{token::Token, Chain, ChainOps},
+    utils::{float::ExtendFloat, table::Table},
+};
+
+use super::{
+    balance::ReplBalanceEntry,
+    report::{match_disposals, LotMethod},
+    transactions::Transaction,
+    Repl,
+};
+
+/// Amount of a token acquired at a given USD price
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CostLot {
+    pub token: Token,
+    pub amount: f64,
+    pub price: f64,
+    pub date: NaiveDate,
+}
+
+pub struct UnrealizedPnl {
+    pub avg_cost: f64,
+    pub pnl: f64,
+    pub pnl_percent: f64,
+}
+
+/// Formats a USD price, keeping enough decimals for low priced tokens
+pub fn format_price(price: f64) -> String {
+    price.round_to_fixed_string(if price.abs() >= 1.0 { 2 } else { 6 })
+}
+
+impl Repl {
+    /// Finds the native token or a tracked token of the chain by symbol or address
+    pub(super) fn find_token(&self, chain: &Chain, token: &str) -> Result<Token, String> {
+        let native_token = &chain.properties.native_token;
+        if native_token.symbol.eq_ignore_ascii_case(token) || native_token.address == token {
+            return Ok(native_token.clone());
+        }
+        let token_address = chain.parse_token_address(token);
+        match self.tokens_of_chain(chain).find(|(_, t)| {
+            t.symbol.eq_ignore_ascii_case(token) || Some(&t.address) == token_address.as_ref()
+        }) {
+            Some((_, t)) => Ok(t.clone()),
+            None => Err(format!(
+                "Found no {} token corresponding to {token:?}",
+                chain.properties.name
+            )),
+        }
+    }
+    /// Manual lots followed by the acquisitions of the imported transactions, paired with
+    /// their chain-id
+    pub(super) fn get_cost_lots(&self, transactions: &[Transaction]) -> Vec<(String, CostLot)> {
+        let mut lots = self.config.cost_lots.clone();
+        lots.extend(
+            transactions
+                .iter()
+                .filter_map(|t| Some((t.chain.clone(), t.to_lot()?))),
+        );
+        lots
+    }
+    /// Lots left once the disposals of the imported transactions are matched against them,
+    /// first in first out
+    pub(super) fn get_remaining_lots(&self) -> Result<Vec<(String, CostLot)>, String> {
+        let transactions = self.read_transactions()?;
+        let mut lots = self.get_cost_lots(&transactions);
+        match_disposals(&mut lots, &transactions, LotMethod::Fifo);
+        Ok(lots)
+    }
+    /// Average USD price paid per unit of token across its lots
+    fn get_average_cost(
+        lots: &[(String, CostLot)],
+        chain_id: &str,
+        token_address: &str,
+    ) -> Option<f64> {
+        let (amount, cost) = lots
+            .iter()
+            .filter(|(c_id, lot)| c_id == chain_id && lot.token.address == token_address)
+            .fold((0.0, 0.0), |(amount, cost), (_, lot)| {
+                (amount + lot.amount, cost + lot.amount * lot.price)
+            });
+        (amount > 0.0).then(|| cost / amount)
+    }
+    pub(super) fn get_unrealized_pnl(
+        &self,
+        lots: &[(String, CostLot)],
+        entry: &ReplBalanceEntry,
+    ) -> Option<UnrealizedPnl> {
+        // Positions, lending and NFTs are not lots of a token
+        if entry.marker.is_some() {
+            return None;
+        }
+        let chain_id = self.get_chain_by_name(&entry.chain)?.properties.get_id();
+        let avg_cost = Self::get_average_cost(lots, &chain_id, &entry.token.address)?;
+        let cost = avg_cost * entry.token.format(&entry.balance_native);
+        let pnl = entry.balance_usd - cost;
+        Some(UnrealizedPnl {
+            avg_cost,
+            pnl,
+            pnl_percent: if cost > 0.0 { pnl / cost * 100.0 } else { 0.0 },
+        })
+    }
+    pub(super) fn handle_cost(&mut self, command_parts: &[&str]) -> Result<(), String> {
+        match command_parts.len() {
+            0 => {
+                if self.config.cost_lots.is_empty() {
+                    println!("You have no cost basis lots");
+                    return Ok(());
+                }
+                let mut rows = self
+                    .config
+                    .cost_lots
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (chain_id, lot))| {
+                        Vec::from([
+                            (i + 1).to_string(),
+                            chain_id.clone(),
+                            lot.token.symbol.clone(),
+                            lot.amount.to_string(),
+                            format_price(lot.price),
+                            lot.date.to_string(),
+                        ])
+                    })
+                    .collect::<Vec<_>>();
+                rows.insert(
+                    0,
+                    Vec::from([
+                        "ID".to_string(),
+                        "Chain".to_string(),
+                        "Token".to_string(),
+                        "Amount".to_string(),
+                        "Price (USD)".to_string(),
+                        "Date".to_string(),
+                    ]),
+                );
+                let mut t = Table::from(rows);
+                t.title = "Cost basis lots".to_string();
+                println!("{t}");
+                Ok(())
+            }
+            2 => match command_parts[0] {
+                "rm" => match command_parts[1].parse::<usize>() {
+                    Ok(x) if x >= 1 && x <= self.config.cost_lots.len() => {
+                        self.config.cost_lots.remove(x - 1);
+                        self.store_config_to_data_file()
+                    }
+                    _ => Err(format!("There is no lot with id {:?}", command_parts[1])),
+                },
+                _ => Repl::get_unknown_option_expecting_err("rm"),
+            },
+            6 => match command_parts[0] {
+                "add" => {
+                    let chain_id = command_parts[1];
+                    let chain = self.find_chain(chain_id)?;
+                    let token = self.find_token(chain, command_parts[2])?;
+                    let amount = match command_parts[3].parse::<f64>() {
+                        Ok(x) if x > 0.0 => x,
+                        _ => return Err("Invalid amount".to_string()),
+                    };
+                    let price = match command_parts[4].parse::<f64>() {
+                        Ok(x) if x >= 0.0 => x,
+                        _ => return Err("Invalid price".to_string()),
+                    };
+                    let date = match NaiveDate::parse_from_str(command_parts[5], "%Y-%m-%d") {
+                        Ok(x) => x,
+                        _ => return Err("Invalid date, expecting YYYY-MM-DD".to_string()),
+                    };
+                    self.config.cost_lots.push((
+                        chain_id.to_string(),
+                        CostLot {
+                            token,
+                            amount,
+                            price,
+                            date,
+                        },
+                    ));
+                    self.store_config_to_data_file()
+                }
+                _ => Repl::get_unknown_option_expecting_err("add"),
+            },
+            _ => Repl::get_bad_argument_count_err(),
+        }
+    }
+}
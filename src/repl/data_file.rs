@@ -1,29 +1,54 @@
 use std::path::PathBuf;
 
 pub static DATA_FILE: &str = ".bop-data";
+pub static HISTORY_FILE: &str = ".bop-history";
+pub static TRANSACTIONS_FILE: &str = ".bop-transactions";
+pub static COMMAND_HISTORY_FILE: &str = ".bop-commands";
 
-pub fn get_data_file_path() -> Result<PathBuf, String> {
+pub fn get_file_path(file: &str) -> Result<PathBuf, String> {
     let home = match dirs::config_dir() {
         Some(x) => x,
         None => return Err("Could not find config directory".to_string()),
     };
-    Ok(home.join(DATA_FILE))
+    Ok(home.join(file))
 }
 
-pub fn data_file_exists() -> Result<bool, String> {
-    Ok(std::fs::metadata(get_data_file_path()?).is_ok())
+pub fn file_exists(file: &str) -> Result<bool, String> {
+    Ok(std::fs::metadata(get_file_path(file)?).is_ok())
 }
 
-pub fn read_data_file() -> Result<Vec<u8>, String> {
-    match std::fs::read(get_data_file_path()?) {
+pub fn read_file(file: &str) -> Result<Vec<u8>, String> {
+    match std::fs::read(get_file_path(file)?) {
         Ok(x) => Ok(x),
-        _ => Err("Could not read data file".to_string()),
+        _ => Err(format!("Could not read {file}")),
     }
 }
 
-pub fn write_data_file(contents: &[u8]) -> Result<(), String> {
-    match std::fs::write(get_data_file_path()?, contents) {
+pub fn write_file(file: &str, contents: &[u8]) -> Result<(), String> {
+    match std::fs::write(get_file_path(file)?, contents) {
         Ok(_) => Ok(()),
-        _ => Err("Could not write data file".to_string()),
+        _ => Err(format!("Could not write {file}")),
     }
 }
+
+pub fn remove_file(file: &str) -> Result<(), String> {
+    if !file_exists(file)? {
+        return Ok(());
+    }
+    match std::fs::remove_file(get_file_path(file)?) {
+        Ok(_) => Ok(()),
+        _ => Err(format!("Could not remove {file}")),
+    }
+}
+
+pub fn data_file_exists() -> Result<bool, String> {
+    file_exists(DATA_FILE)
+}
+
+pub fn read_data_file() -> Result<Vec<u8>, String> {
+    read_file(DATA_FILE)
+}
+
+pub fn write_data_file(contents: &[u8]) -> Result<(), String> {
+    write_file(DATA_FILE, contents)
+}
@@ -3,7 +3,7 @@ use crate::{
     utils::spinner::Spinner,
 };
 
-use super::Repl;
+use super::{output::OutputFormat, Repl};
 
 fn make_usdc(addr: &str) -> Token {
     Token::hardcode("USDC", addr, 6)
@@ -59,7 +59,9 @@ impl Default for Repl {
                     make_usdt("0xdac17f958d2ee523a2206206994597c13d831ec7"),
                     make_usdc("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"),
                 ]),
-            ),
+            )
+            .with_position_manager("0xC36442b4a4522E871399CD717aBDD847Ab11FE88")
+            .with_lending_pool("0x87870Bca3F3fD6335C3F4ce8392D69350B4fA4E2"),
             Chain::new(
                 ChainType::Evm,
                 Vec::from([
@@ -93,7 +95,9 @@ impl Default for Repl {
                     make_usdt("0x55d398326f99059ff775485246999027b3197955"),
                     make_usdc("0x8ac76a51cc950d9822d68b83fe1ad97b32cd580d"),
                 ]),
-            ),
+            )
+            .with_position_manager("0x7b8A01B39D58278b5DE7e48c8449c9f4F5170613")
+            .with_lending_pool("0x6807dc923806fE8Fd134338EABCA509979a7e0cB"),
             Chain::new(
                 ChainType::Evm,
                 Vec::from([
@@ -110,7 +114,9 @@ impl Default for Repl {
                     make_usdt("0xFd086bC7CD5C481DCC9C85ebE478A1C0b69FCbb9"),
                     make_usdc("0xaf88d065e77c8cC2239327C5EDb3A432268e5831"),
                 ]),
-            ),
+            )
+            .with_position_manager("0xC36442b4a4522E871399CD717aBDD847Ab11FE88")
+            .with_lending_pool("0x794a61358D6845594F94dc1DB02A252b5b4814aD"),
             Chain::new(
                 ChainType::Evm,
                 Vec::from([
@@ -127,7 +133,8 @@ impl Default for Repl {
                     make_usdt("0x9702230A8Ea53601f5cD2dc00fDBc13d4dF4A8c7"),
                     make_usdc("0xB97EF9Ef8734C71904D8002F8b6Bc66Dd9c48a6E"),
                 ]),
-            ),
+            )
+            .with_lending_pool("0x794a61358D6845594F94dc1DB02A252b5b4814aD"),
             Chain::new(
                 ChainType::Evm,
                 Vec::from([
@@ -144,7 +151,9 @@ impl Default for Repl {
                     make_usdt("0xc2132d05d31c914a87c6611c10748aeb04b58e8f"),
                     make_usdc("0x3c499c542cEF5E3811e1192ce70d8cC03d5c3359"),
                 ]),
-            ),
+            )
+            .with_position_manager("0xC36442b4a4522E871399CD717aBDD847Ab11FE88")
+            .with_lending_pool("0x794a61358D6845594F94dc1DB02A252b5b4814aD"),
             Chain::new(
                 ChainType::Evm,
                 Vec::from([
@@ -209,7 +218,9 @@ impl Default for Repl {
                     make_usdt("0x94b008aa00579c1307b0ef2c499ad98a8ce58e58"),
                     make_usdc("0x0b2C639c533813f4Aa9D7837CAf62653d097Ff85"),
                 ]),
-            ),
+            )
+            .with_position_manager("0xC36442b4a4522E871399CD717aBDD847Ab11FE88")
+            .with_lending_pool("0x794a61358D6845594F94dc1DB02A252b5b4814aD"),
             Chain::new(
                 ChainType::Evm,
                 Vec::from([
@@ -385,6 +396,13 @@ impl Default for Repl {
             config: super::ReplConfig::default(),
             spinner: Spinner::new(),
             secret: None,
+            given_password: None,
+            output: OutputFormat::default(),
+            command_history: Vec::new(),
+            command_history_task: None,
+            is_running_script: false,
+            has_unsaved_config: false,
+            is_script_exited: false,
         }
     }
 }
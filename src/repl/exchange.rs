@@ -0,0 +1,157 @@
+use std::{collections::HashMap, str::FromStr};
+
+use futures::{stream, StreamExt};
+use itertools::Itertools;
+
+use crate::{
+    dexscreener,
+    exchange::{Exchange, ExchangeOps, ExchangeType, EXCHANGE_TYPES},
+    utils::table::Table,
+};
+
+use super::{balance::ReplBalanceEntry, Repl};
+
+impl Repl {
+    fn find_exchange(&self, name: &str) -> Option<usize> {
+        self.config
+            .exchanges
+            .iter()
+            .position(|e| e.name.eq_ignore_ascii_case(name))
+    }
+    /// Balance entries of the exchange accounts priced by symbol, exchanges that could not
+    /// be queried are reported and skipped
+    pub(super) async fn get_exchange_balances(&self) -> Vec<ReplBalanceEntry> {
+        let exchanges = &self.config.exchanges;
+        self.spinner.set_total(exchanges.len());
+        self.spinner.start(Some("Querying exchanges..."));
+        let results = stream::iter(exchanges.iter())
+            .map(async |exchange| {
+                let result = exchange.get_balances().await;
+                self.spinner.inc_progress();
+                result
+            })
+            .buffered(5)
+            .collect::<Vec<_>>()
+            .await;
+        self.spinner.stop();
+        let mut holdings = Vec::new();
+        for (exchange, result) in exchanges.iter().zip(results) {
+            match result {
+                Ok(balances) => holdings.extend(balances.into_iter().map(|b| (exchange, b))),
+                Err(err) => eprintln!("Could not query {}: {err}", exchange.name),
+            }
+        }
+        let symbols = holdings
+            .iter()
+            .map(|(_, (symbol, _))| symbol.as_str())
+            .unique()
+            .collect::<Vec<_>>();
+        self.spinner.set_total(symbols.len());
+        self.spinner
+            .start(Some("Fetching exchange asset prices..."));
+        let prices = stream::iter(symbols.iter())
+            .map(async |symbol| {
+                let price = dexscreener::pairs::search_price(symbol).await;
+                self.spinner.inc_progress();
+                (symbol.to_string(), price)
+            })
+            .buffered(5)
+            .collect::<HashMap<_, _>>()
+            .await;
+        self.spinner.stop();
+        holdings
+            .iter()
+            .map(|(exchange, (symbol, amount))| {
+                ReplBalanceEntry::off_chain(
+                    &exchange.exchange_type.label(),
+                    &exchange.name,
+                    symbol,
+                    *amount,
+                    prices.get(symbol).copied().flatten().unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
+    pub(super) fn handle_exchange(&mut self, command_parts: &[&str]) -> Result<(), String> {
+        match command_parts.len() {
+            0 => {
+                if self.config.exchanges.is_empty() {
+                    println!("You have no exchange accounts");
+                    return Ok(());
+                }
+                let mut rows = self
+                    .config
+                    .exchanges
+                    .iter()
+                    .map(|e| {
+                        // Only a hint of the key is displayed
+                        let key_hint = e.api_key.chars().take(6).collect::<String>();
+                        Vec::from([
+                            e.name.clone(),
+                            e.exchange_type.label(),
+                            format!("{key_hint}..."),
+                            e.base_url().to_string(),
+                        ])
+                    })
+                    .collect::<Vec<_>>();
+                rows.insert(
+                    0,
+                    Vec::from([
+                        "Name".to_string(),
+                        "Exchange".to_string(),
+                        "API key".to_string(),
+                        "API URL".to_string(),
+                    ]),
+                );
+                let mut t = Table::from(rows);
+                t.title = "Exchanges".to_string();
+                println!("{t}");
+                println!(
+                    "Available exchange-types: {}",
+                    EXCHANGE_TYPES.iter().map(|e| e.to_string()).join(", ")
+                );
+                Ok(())
+            }
+            2 => match command_parts[0] {
+                "rm" => match self.find_exchange(command_parts[1]) {
+                    Some(i) => {
+                        self.config.exchanges.remove(i);
+                        self.store_config_to_data_file()
+                    }
+                    None => Err(format!(
+                        "There is no exchange account named {:?}",
+                        command_parts[1]
+                    )),
+                },
+                _ => Repl::get_unknown_option_expecting_err("rm"),
+            },
+            5 | 6 => match command_parts[0] {
+                "add" => {
+                    let exchange_type = ExchangeType::from_str(command_parts[1])?;
+                    let name = command_parts[2];
+                    if self.find_exchange(name).is_some() {
+                        return Err(format!(
+                            "There is already an exchange account named {name:?}"
+                        ));
+                    }
+                    let base_url = match command_parts.get(5) {
+                        Some(url) if reqwest::Url::from_str(url).is_err() => {
+                            return Err(format!("Invalid URL {url:?}"))
+                        }
+                        url => url.map(|u| u.to_string()),
+                    };
+                    self.config.exchanges.push(Exchange {
+                        exchange_type,
+                        name: name.to_string(),
+                        api_key: command_parts[3].to_string(),
+                        api_secret: command_parts[4].to_string(),
+                        base_url,
+                    });
+                    self.store_config_to_data_file()
+                }
+                _ => Repl::get_unknown_option_expecting_err("add"),
+            },
+            _ => Repl::get_bad_argument_count_err(),
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use std::borrow::Cow;
+
+use itertools::Itertools;
+use rustyline::{
+    completion::{Completer, Pair},
+    highlight::Highlighter,
+    hint::{Hint, Hinter},
+    validate::Validator,
+    Context, Helper,
+};
+
+use crate::{chain::chain_type::CHAIN_TYPES, exchange::EXCHANGE_TYPES, utils::text::StylizedText};
+
+use super::{Repl, HELP};
+
+/// Commands that are not part of the help text
+static EXTRA_COMMANDS: [&str; 4] = ["help", "?", "exit", "quit"];
+
+/// Inline hint of the arguments left to type
+pub struct CommandHint {
+    display: String,
+    /// Part of the hint that can be accepted as is, when it completes a subcommand
+    completion: Option<String>,
+}
+
+impl Hint for CommandHint {
+    fn display(&self) -> &str {
+        &self.display
+    }
+    fn completion(&self) -> Option<&str> {
+        self.completion.as_deref()
+    }
+}
+
+/// Completes and hints commands following the usage lines of the help text
+pub struct ReplHelper {
+    /// Words of each usage line, e.g. `["chain", "set", "[chain]", "[url]"]`
+    usages: Vec<Vec<String>>,
+    chain_ids: Vec<String>,
+    /// Addresses and aliases
+    accounts: Vec<String>,
+    /// Vec of chain-id and token address
+    tokens: Vec<(String, String)>,
+}
+
+impl ReplHelper {
+    fn is_argument(word: &str) -> bool {
+        word.starts_with('[')
+    }
+    /// Options of an argument that only takes fixed values, e.g. `[csv|md|html]`
+    fn get_argument_options(argument: &str) -> Option<Vec<&str>> {
+        let options = argument
+            .trim_matches(['[', ']', '?'])
+            .split('|')
+            .collect::<Vec<_>>();
+        (options.len() > 1 && options.iter().all(|o| !o.contains('-'))).then_some(options)
+    }
+    fn matches(usage_word: &str, word: &str) -> bool {
+        if !Self::is_argument(usage_word) {
+            return usage_word == word;
+        }
+        match Self::get_argument_options(usage_word) {
+            Some(options) => options.contains(&word),
+            None => true,
+        }
+    }
+    /// Usage lines that the typed words follow and that expect more words
+    fn find_usages<'a>(&'a self, words: &'a [&str]) -> impl Iterator<Item = &'a Vec<String>> {
+        self.usages.iter().filter(move |usage| {
+            usage.len() > words.len()
+                && usage
+                    .iter()
+                    .zip(words)
+                    .all(|(usage_word, word)| Self::matches(usage_word, word))
+        })
+    }
+    fn get_candidates(&self, usage: &[String], words: &[&str]) -> Vec<String> {
+        let usage_word = usage[words.len()].as_str();
+        if !Self::is_argument(usage_word) {
+            return Vec::from([usage_word.to_string()]);
+        }
+        if let Some(options) = Self::get_argument_options(usage_word) {
+            return options.iter().map(|o| o.to_string()).collect();
+        }
+        // Tokens are listed for the chain typed before them
+        let chain_id = usage
+            .iter()
+            .zip(words)
+            .find(|(usage_word, _)| *usage_word == "[chain]")
+            .map(|(_, word)| *word);
+        match (usage[0].as_str(), usage_word.trim_matches(['[', ']', '?'])) {
+            (_, "chain") => self.chain_ids.clone(),
+            (_, "chain-type") => CHAIN_TYPES.iter().map(|c| c.to_string()).collect(),
+            (_, "exchange-type") => EXCHANGE_TYPES.iter().map(|e| e.to_string()).collect(),
+            (_, "account") => self.accounts.clone(),
+            ("token", "address") | ("cost", "token") => self
+                .tokens
+                .iter()
+                .filter(|(c_id, _)| Some(c_id.as_str()) == chain_id)
+                .map(|(_, address)| address.clone())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+    /// Splits the line into the words already typed and the one being typed
+    fn split_line(line: &str) -> (Vec<&str>, &str) {
+        let start = line
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map(|(i, c)| i + c.len_utf8())
+            .unwrap_or(0);
+        (line[..start].split_whitespace().collect(), &line[start..])
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let (words, partial) = Self::split_line(&line[..pos]);
+        let mut candidates = self
+            .find_usages(&words)
+            .flat_map(|usage| self.get_candidates(usage, &words))
+            .collect::<Vec<_>>();
+        if words.is_empty() {
+            candidates.extend(EXTRA_COMMANDS.iter().map(|c| c.to_string()));
+        }
+        let pairs = candidates
+            .into_iter()
+            .filter(|c| c.starts_with(partial))
+            .unique()
+            .sorted()
+            .map(|c| Pair {
+                display: c.clone(),
+                replacement: format!("{c} "),
+            })
+            .collect();
+        Ok((pos - partial.len(), pairs))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = CommandHint;
+    fn hint(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> Option<CommandHint> {
+        if pos < line.len() || line.trim().is_empty() {
+            return None;
+        }
+        let (words, partial) = Self::split_line(line);
+        let hint = self.find_usages(&words).find_map(|usage| {
+            let next = &usage[words.len()];
+            let rest = usage[words.len() + 1..].join(" ");
+            if partial.is_empty() {
+                return Some(CommandHint {
+                    display: usage[words.len()..].join(" "),
+                    completion: None,
+                });
+            }
+            let completion = if Self::is_argument(next) {
+                None
+            } else {
+                Some(next.strip_prefix(partial)?.to_string())
+            };
+            let display = match (&completion, rest.is_empty()) {
+                (Some(completion), true) => completion.clone(),
+                (Some(completion), false) => format!("{completion} {rest}"),
+                (None, true) => return None,
+                (None, false) => format!(" {rest}"),
+            };
+            Some(CommandHint {
+                display,
+                completion,
+            })
+        });
+        hint
+    }
+}
+
+impl Highlighter for ReplHelper {
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        Cow::Owned(hint.to_dimmed())
+    }
+}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+impl Repl {
+    /// Helper reflecting the current chains, accounts and tokens
+    pub(super) fn create_helper(&self) -> ReplHelper {
+        let usages = HELP
+            .trim()
+            .lines()
+            .filter_map(|line| line.split_once(" - "))
+            .map(|(usage, _)| usage.split_whitespace().map(|w| w.to_string()).collect())
+            .collect();
+        ReplHelper {
+            usages,
+            chain_ids: self.chains.iter().map(|c| c.properties.get_id()).collect(),
+            accounts: self
+                .config
+                .accounts
+                .iter()
+                .flat_map(|(_, address, alias)| [Some(address.clone()), alias.clone()])
+                .flatten()
+                .collect(),
+            tokens: self
+                .config
+                .tokens
+                .iter()
+                .map(|(chain_id, t)| (chain_id.clone(), t.address.clone()))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReplHelper;
+
+    #[test]
+    fn split_line_separates_partial_word() {
+        assert_eq!(ReplHelper::split_line(""), (Vec::new(), ""));
+        assert_eq!(ReplHelper::split_line("bal"), (Vec::new(), "bal"));
+        assert_eq!(
+            ReplHelper::split_line("token add "),
+            (Vec::from(["token", "add"]), "")
+        );
+        assert_eq!(
+            ReplHelper::split_line("token  add eth"),
+            (Vec::from(["token", "add"]), "eth")
+        );
+    }
+
+    #[test]
+    fn split_line_handles_multi_byte_whitespace() {
+        assert_eq!(
+            ReplHelper::split_line("token\u{3000}add"),
+            (Vec::from(["token"]), "add")
+        );
+        assert_eq!(
+            ReplHelper::split_line("token\u{a0}"),
+            (Vec::from(["token"]), "")
+        );
+    }
+}
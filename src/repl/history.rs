@@ -0,0 +1,418 @@
+use chrono::{Local, TimeZone};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::{
+    chart::{sparkline, terminal_width, Chart},
+    float::ExtendFloat,
+    table::Table,
+};
+
+use super::{balance::ReplBalances, data_file::HISTORY_FILE, Repl};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SnapshotEntry {
+    pub account: String,
+    pub chain: String,
+    pub symbol: String,
+    pub address: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub marker: Option<String>,
+    pub amount: f64,
+    pub balance_usd: f64,
+}
+
+impl SnapshotEntry {
+    /// Whether both entries refer to the same holding
+    pub fn is_same_holding(&self, other: &SnapshotEntry) -> bool {
+        self.account == other.account
+            && self.chain == other.chain
+            && self.address == other.address
+            && self.marker == other.marker
+    }
+    pub fn price(&self) -> f64 {
+        if self.amount == 0.0 {
+            return 0.0;
+        }
+        self.balance_usd / self.amount
+    }
+    pub fn label(&self) -> String {
+        match &self.marker {
+            Some(marker) => format!("{} [{marker}]", self.symbol),
+            None => self.symbol.clone(),
+        }
+    }
+}
+
+/// Portfolio state recorded after each `balance`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Snapshot {
+    /// Unix timestamp in seconds
+    pub timestamp: i64,
+    pub entries: Vec<SnapshotEntry>,
+    pub total_usd: f64,
+}
+
+impl Snapshot {
+    pub fn format_date(&self) -> String {
+        match Local.timestamp_opt(self.timestamp, 0).single() {
+            Some(x) => x.format("%Y-%m-%d %H:%M").to_string(),
+            None => self.timestamp.to_string(),
+        }
+    }
+}
+
+/// Change of a single holding between two snapshots
+struct HoldingChange<'a> {
+    entry: &'a SnapshotEntry,
+    label: String,
+    amount: f64,
+    amount_change: f64,
+    /// USD change caused by the price moving
+    price_change: f64,
+    /// USD change caused by the amount changing
+    quantity_change: f64,
+}
+
+impl From<&ReplBalances> for Snapshot {
+    fn from(balances: &ReplBalances) -> Self {
+        Self {
+            timestamp: Local::now().timestamp(),
+            entries: balances
+                .entries
+                .iter()
+                .map(|b| SnapshotEntry {
+                    account: b.account.clone(),
+                    chain: b.chain.clone(),
+                    symbol: b.token.symbol.clone(),
+                    address: b.token.address.clone(),
+                    marker: b.marker.map(|m| m.to_string()),
+                    amount: b.token.format(&b.balance_native),
+                    balance_usd: b.balance_usd,
+                })
+                .collect(),
+            total_usd: balances.total_usd(),
+        }
+    }
+}
+
+impl Repl {
+    pub(super) fn read_history(&self) -> Result<Vec<Snapshot>, String> {
+        self.read_encrypted_file(HISTORY_FILE)
+    }
+    pub(super) fn store_history(&self, history: &[Snapshot]) -> Result<(), String> {
+        self.store_encrypted_file(HISTORY_FILE, &history)
+    }
+    pub(super) fn record_snapshot(&self, balances: &ReplBalances) -> Result<(), String> {
+        let mut history = self.read_history()?;
+        history.push(Snapshot::from(balances));
+        self.store_history(&history)
+    }
+    /// Parses a snapshot id as displayed by `history`, returning its index
+    pub(super) fn parse_snapshot_id(history: &[Snapshot], id: &str) -> Result<usize, String> {
+        match id.parse::<usize>() {
+            Ok(x) if x >= 1 && x <= history.len() => Ok(x - 1),
+            _ => Err(format!("There is no snapshot with id {id:?}")),
+        }
+    }
+    fn display_snapshot(snapshot: &Snapshot) {
+        let mut rows = snapshot
+            .entries
+            .iter()
+            .map(|e| {
+                Vec::from([
+                    e.account.clone(),
+                    e.chain.clone(),
+                    e.label(),
+                    e.amount.to_string(),
+                    e.balance_usd.round_to_fixed_string(2),
+                ])
+            })
+            .collect::<Vec<_>>();
+        rows.insert(
+            0,
+            Vec::from([
+                "Account".to_string(),
+                "Chain".to_string(),
+                "Token".to_string(),
+                "Balance".to_string(),
+                "Balance (USD)".to_string(),
+            ]),
+        );
+        let mut t = Table::from(rows);
+        t.title = format!("Balances at {}", snapshot.format_date());
+        println!("{t}");
+        println!(
+            "Holdings: {}\nBalance: {} USD",
+            snapshot.entries.len(),
+            snapshot.total_usd.round_to_fixed_string(2)
+        );
+    }
+    /// Displays how the portfolio changed from `old` to `new`, splitting the USD change of
+    /// each holding into the part caused by its price moving and the part caused by its
+    /// amount changing
+    pub(super) fn display_diff(old: &Snapshot, new: &Snapshot) {
+        let changes = new
+            .entries
+            .iter()
+            .map(|e| (old.entries.iter().find(|o| o.is_same_holding(e)), Some(e)))
+            .chain(
+                old.entries
+                    .iter()
+                    .filter(|o| !new.entries.iter().any(|e| e.is_same_holding(o)))
+                    .map(|o| (Some(o), None)),
+            )
+            .map(|(old_entry, new_entry)| {
+                let (old_amount, old_price) =
+                    old_entry.map_or((0.0, 0.0), |o| (o.amount, o.price()));
+                let (new_amount, new_price) =
+                    new_entry.map_or((0.0, old_price), |e| (e.amount, e.price()));
+                let price_change = (new_price - old_price) * old_amount;
+                let quantity_change = (new_amount - old_amount) * new_price;
+                let entry = new_entry.or(old_entry).unwrap();
+                let label = match (old_entry, new_entry) {
+                    (None, _) => format!("{} (new)", entry.label()),
+                    (_, None) => format!("{} (gone)", entry.label()),
+                    _ => entry.label(),
+                };
+                HoldingChange {
+                    entry,
+                    label,
+                    amount: new_amount,
+                    amount_change: new_amount - old_amount,
+                    price_change,
+                    quantity_change,
+                }
+            })
+            .filter(|c| {
+                c.amount_change != 0.0 || (c.price_change + c.quantity_change).abs() >= 0.01
+            })
+            .collect::<Vec<_>>();
+        let mut rows = changes
+            .iter()
+            .map(|c| {
+                Vec::from([
+                    c.entry.account.clone(),
+                    c.entry.chain.clone(),
+                    c.label.clone(),
+                    c.amount.to_string(),
+                    if c.amount_change > 0.0 {
+                        format!("+{}", c.amount_change)
+                    } else {
+                        c.amount_change.to_string()
+                    },
+                    c.price_change.round_to_signed_string(2),
+                    c.quantity_change.round_to_signed_string(2),
+                    (c.price_change + c.quantity_change).round_to_signed_string(2),
+                ])
+            })
+            .collect::<Vec<_>>();
+        rows.insert(
+            0,
+            Vec::from([
+                "Account".to_string(),
+                "Chain".to_string(),
+                "Token".to_string(),
+                "Balance".to_string(),
+                "Balance change".to_string(),
+                "Price effect (USD)".to_string(),
+                "Quantity effect (USD)".to_string(),
+                "Change (USD)".to_string(),
+            ]),
+        );
+        let mut t = Table::from(rows);
+        t.title = format!("Changes since {}", old.format_date());
+        println!("{t}");
+        let (price_total, quantity_total) = changes.iter().fold((0.0, 0.0), |(p, q), c| {
+            (p + c.price_change, q + c.quantity_change)
+        });
+        let pnl = new.total_usd - old.total_usd;
+        println!(
+            "Balance: {} USD (was {} USD)\nPnL: {} USD{}\nPrice-driven: {} USD\nQuantity-driven: {} USD",
+            new.total_usd.round_to_fixed_string(2),
+            old.total_usd.round_to_fixed_string(2),
+            pnl.round_to_signed_string(2),
+            if old.total_usd != 0.0 {
+                format!(" ({}%)", (pnl / old.total_usd.abs() * 100.0).round_to_signed_string(2))
+            } else {
+                String::new()
+            },
+            price_total.round_to_signed_string(2),
+            quantity_total.round_to_signed_string(2),
+        );
+    }
+    /// Parses a range such as "7d", "4w", "6m" or "1y" into seconds
+    fn parse_range(range: &str) -> Result<i64, String> {
+        let err =
+            format!("Invalid range {range:?}, expecting e.g. \"7d\", \"4w\", \"6m\" or \"1y\"");
+        let unit = match range.chars().last() {
+            Some(x) => x,
+            None => return Err(err),
+        };
+        let count = match range[..range.len() - unit.len_utf8()].parse::<i64>() {
+            Ok(x) if x > 0 => x,
+            _ => return Err(err),
+        };
+        let days = match unit {
+            'd' => 1,
+            'w' => 7,
+            'm' => 30,
+            'y' => 365,
+            _ => return Err(err),
+        };
+        match count.checked_mul(days * 24 * 60 * 60) {
+            Some(x) => Ok(x),
+            None => Err(err),
+        }
+    }
+    fn display_history_chart(history: &[Snapshot], range: Option<&str>) -> Result<(), String> {
+        let min_timestamp = match range {
+            None | Some("all") => i64::MIN,
+            Some(range) => Local::now()
+                .timestamp()
+                .saturating_sub(Self::parse_range(range)?),
+        };
+        let snapshots = history
+            .iter()
+            .filter(|snapshot| snapshot.timestamp >= min_timestamp)
+            .collect::<Vec<_>>();
+        let (first, last) = match (snapshots.first(), snapshots.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Err("There are no snapshots in range".to_string()),
+        };
+        let mut chart = Chart::from(snapshots.iter().map(|s| s.total_usd).collect::<Vec<_>>());
+        chart.title = "Balance (USD)".to_string();
+        chart.x_labels = (first.format_date(), last.format_date());
+        println!("{chart}");
+        let chains = snapshots
+            .iter()
+            .flat_map(|s| s.entries.iter().map(|e| e.chain.as_str()))
+            .unique()
+            .collect::<Vec<_>>();
+        if chains.len() < 2 {
+            return Ok(());
+        }
+        let chain_total = |snapshot: &Snapshot, chain: &str| {
+            snapshot
+                .entries
+                .iter()
+                .filter(|e| e.chain == chain)
+                .fold(0.0, |sum, e| sum + e.balance_usd)
+        };
+        let chain_width = chains.iter().map(|c| c.len()).max().unwrap_or_default();
+        let trend_width = terminal_width().saturating_sub(chain_width + 20).max(8);
+        let mut rows = chains
+            .iter()
+            .map(|chain| {
+                let values = snapshots
+                    .iter()
+                    .map(|s| chain_total(s, chain))
+                    .collect::<Vec<_>>();
+                Vec::from([
+                    chain.to_string(),
+                    chain_total(last, chain).round_to_fixed_string(2),
+                    sparkline(&values, trend_width),
+                ])
+            })
+            .collect::<Vec<_>>();
+        rows.insert(
+            0,
+            Vec::from([
+                "Chain".to_string(),
+                "Balance (USD)".to_string(),
+                "Trend".to_string(),
+            ]),
+        );
+        let mut t = Table::from(rows);
+        t.title = "Balance by chain".to_string();
+        println!("{t}");
+        Ok(())
+    }
+    pub(super) fn handle_history(&mut self, command_parts: &[&str]) -> Result<(), String> {
+        // Clearing the command history must not depend on the snapshots being readable
+        if command_parts == ["clear"] {
+            self.clear_command_history()?;
+            println!("Command history cleared");
+            return Ok(());
+        }
+        let mut history = self.read_history()?;
+        match command_parts.len() {
+            0 => {
+                let mut rows = history
+                    .iter()
+                    .enumerate()
+                    .map(|(i, snapshot)| {
+                        Vec::from([
+                            (i + 1).to_string(),
+                            snapshot.format_date(),
+                            snapshot.entries.len().to_string(),
+                            snapshot.total_usd.round_to_fixed_string(2),
+                        ])
+                    })
+                    .collect::<Vec<_>>();
+                rows.insert(
+                    0,
+                    Vec::from([
+                        "ID".to_string(),
+                        "Date".to_string(),
+                        "Holdings".to_string(),
+                        "Balance (USD)".to_string(),
+                    ]),
+                );
+                let mut t = Table::from(rows);
+                t.title = "Snapshots".to_string();
+                println!("{t}");
+                Ok(())
+            }
+            1 => match command_parts[0] {
+                "chart" => Self::display_history_chart(&history, None),
+                _ => Repl::get_unknown_option_expecting_or_err(&["chart", "clear"]),
+            },
+            2 => match command_parts[0] {
+                "chart" => Self::display_history_chart(&history, Some(command_parts[1])),
+                "show" => {
+                    let index = Self::parse_snapshot_id(&history, command_parts[1])?;
+                    Self::display_snapshot(&history[index]);
+                    Ok(())
+                }
+                "rm" => {
+                    let index = Self::parse_snapshot_id(&history, command_parts[1])?;
+                    history.remove(index);
+                    self.store_history(&history)
+                }
+                "prune" => {
+                    let days = match command_parts[1].parse::<u32>() {
+                        Ok(x) => x,
+                        _ => return Err("Invalid number of days".to_string()),
+                    };
+                    let min_timestamp = Local::now().timestamp() - i64::from(days) * 24 * 60 * 60;
+                    let len = history.len();
+                    history.retain(|snapshot| snapshot.timestamp >= min_timestamp);
+                    self.store_history(&history)?;
+                    println!("{} snapshots deleted", len - history.len());
+                    Ok(())
+                }
+                _ => Repl::get_unknown_option_expecting_or_err(&["chart", "show", "rm", "prune"]),
+            },
+            _ => Repl::get_bad_argument_count_err(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Repl;
+
+    #[test]
+    fn parse_range_converts_to_seconds() {
+        assert_eq!(Repl::parse_range("7d"), Ok(7 * 24 * 60 * 60));
+        assert_eq!(Repl::parse_range("1y"), Ok(365 * 24 * 60 * 60));
+    }
+
+    #[test]
+    fn parse_range_rejects_invalid_counts() {
+        for range in ["-5d", "0w", "d", "5x", "", "99999999999999999y"] {
+            assert!(Repl::parse_range(range).is_err(), "{range:?} was accepted");
+        }
+    }
+}
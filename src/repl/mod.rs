@@ -1,32 +1,112 @@
+mod allocation;
+mod asset;
+mod balance;
+mod command_history;
+mod cost;
 mod data_file;
 mod default;
+mod exchange;
+mod helper;
+mod history;
+mod nft;
+mod output;
+mod report;
+mod script;
+mod transactions;
+#[cfg(feature = "tui")]
+mod tui;
+mod watch;
 
-use std::{collections::HashMap, fmt::Display, str::FromStr};
+use std::{collections::HashMap, fmt::Display, str::FromStr, thread::JoinHandle};
 
 use age::secrecy::{ExposeSecret, SecretString};
-use data_file::{data_file_exists, read_data_file, write_data_file};
-use futures::{stream, StreamExt};
-use itertools::Itertools;
-use num_bigint::BigUint;
+use asset::ManualAsset;
+use cost::CostLot;
+use data_file::{
+    data_file_exists, file_exists, read_data_file, read_file, write_data_file, write_file,
+};
+use helper::ReplHelper;
+pub use output::OutputFormat;
 use reqwest::{header::HeaderMap, Url};
-use rustyline::{error::ReadlineError, DefaultEditor};
-use serde::{Deserialize, Serialize};
+use rustyline::{error::ReadlineError, history::DefaultHistory, Editor};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::json;
 
 use crate::{
     chain::{
         chain_type::{ChainType, CHAIN_TYPES},
+        nft::NftCollection,
         token::Token,
         Chain, ChainOps,
     },
-    dexscreener,
-    utils::{
-        float::ExtendFloat, retry::handle_retry_indexed, spinner::Spinner, table::Table,
-        text::StylizedText,
-    },
+    exchange::Exchange,
+    utils::{spinner::Spinner, table::Table, text::StylizedText},
 };
 
 static BOOK_OF_PROFITS: &str = "Book of Profits";
 
+/// Usage lines in the "command [argument] - description" format
+static HELP: &str = r###"
+chain - Display available chain-types and chains
+    chain [chain] - Show chain information
+    chain set [chain] [url] - Modify chain RPC url
+    chain rm [chain] - Remove custom chain RPC url
+    chain toggle [chain] - Toggle chain
+    chain toggle-all [chain-type] - Toggle all chains of chain-type
+    chain checksum [chain] [chain-id?] - Checksum addresses of an EVM chain following EIP-1191 with the chain id, EIP-55 when none
+account - Display accounts
+    account add [chain-type] [address] [alias?] - Add new address to track, optionally pass an alias
+    account rm [account] - Remove account
+token - Display tokens
+    token add [chain] [address] - Add new token
+    token rm [chain] [address] - Remove token
+    token scan [chain] [account] - Automatically scan account and add tokens
+balance - Display global balance and record a snapshot of it
+    balance diff [snapshot-id?] - Compare global balance to a snapshot, the last one by default
+    balance alloc [token|chain|type|account?] - Display allocation by token, chain, chain-type or account, token by default
+    balance threshold [percent?] - Show or set the share above which an allocation is flagged
+    balance watch [seconds?] - Refresh balance on an interval, 60 seconds by default, highlighting changes until a key is pressed
+    balance export [csv|md|html] [path] - Export balance with breakdowns by chain and account to a file, csv sections starting with their title
+history - Display recorded balance snapshots
+    history chart [range?] - Chart balance over a range like 7d, 4w, 6m or 1y, all by default
+    history show [id] - Show snapshot
+    history rm [id] - Delete snapshot
+    history prune [days] - Delete snapshots older than the given number of days
+    history clear - Clear the encrypted history of entered commands
+nft - Display tracked NFT collections
+    nft balance - Display NFT holdings valued at floor price
+    nft add [chain] [contract] [token-ids?] - Track EVM collection, ERC-1155 requires comma separated token ids
+    nft list [chain] [account] - List every NFT owned by a Solana or Ton account
+    nft rm [chain] [contract] - Stop tracking collection
+    nft floor [chain] [contract] [usd] - Set a fixed floor price for the collection
+    nft key [api-key] - Set OpenSea API key used to fetch floor prices
+    nft toggle-balance - Toggle including NFT holdings in the global balance
+rebalance - Display the trades needed to reach the target allocation
+    rebalance set [asset] [percent] - Set target weight of a token symbol, use "stables" to target every stablecoin
+    rebalance rm [asset] - Remove target weight
+    rebalance tolerance [percent] - Set drift tolerated before suggesting a trade
+asset - Display manual assets
+    asset add [name] [amount] [symbol|usd-price] - Add off-chain asset priced by symbol through Dexscreener or at a fixed USD price
+    asset edit [name] [amount] [symbol|usd-price?] - Edit amount and optionally price of manual asset
+    asset rm [name] - Remove manual asset
+exchange - Display exchange accounts
+    exchange add [exchange-type] [name] [api-key] [api-secret] [api-url?] - Add exchange account with a read-only API key, optionally overriding the API URL
+    exchange rm [name] - Remove exchange account
+cost - Display cost basis lots
+    cost add [chain] [token] [amount] [price] [date] - Add lot of a native or tracked token bought at a USD price on a YYYY-MM-DD date
+    cost rm [id] - Remove lot
+tx - Display imported transactions
+    tx import [chain] [account] - Import token transfers of the account, acquisitions priced at the time become cost basis lots
+    tx rm [chain] [account] - Remove imported transactions of the account
+report gains [year] [fifo|lifo|hifo] [path?] - Display realized gains of the year matching disposals to lots, optionally exported as CSV
+config - Export BoP config in plain text
+    config password - Change password
+    config toggle-history - Toggle keeping an encrypted history of entered commands
+source [file] - Run commands of a file line by line, with # comments, set -e and NAME=value variables
+tui - Open a full-screen dashboard, when built with the tui feature
+set output [table|json] - Display balance, account, token and chain as tables or JSON
+"###;
+
 #[derive(Serialize, Deserialize, Default, Debug)]
 pub struct ReplConfig {
     /// Vec of chain-type, account address and optional alias
@@ -41,6 +121,39 @@ pub struct ReplConfig {
     /// Map of chain-id to enabled
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     chains_enabled: HashMap<String, bool>,
+    /// Map of chain-id to the chain id its addresses are checksummed with (EIP-1191)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    eip1191_chain_ids: HashMap<String, u64>,
+    /// Vec of chain-id and NFT collection
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    nfts: Vec<(String, NftCollection)>,
+    /// OpenSea API key used to fetch NFT floor prices
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    opensea_api_key: Option<String>,
+    /// Whether NFT holdings valued at floor price are part of the balance
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    nfts_in_balance: bool,
+    /// Share of the portfolio above which a holding is flagged as concentrated, in percent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    concentration_threshold: Option<f64>,
+    /// Vec of asset and target weight in percent, where an asset is a token symbol or "stables"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    rebalance_targets: Vec<(String, f64)>,
+    /// Drift from the target weight tolerated before suggesting a trade, in percentage points
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rebalance_tolerance: Option<f64>,
+    /// Vec of chain-id and cost basis lot
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    cost_lots: Vec<(String, CostLot)>,
+    /// Off-chain holdings displayed under the "Manual" chain of the balance
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    manual_assets: Vec<ManualAsset>,
+    /// Exchange accounts displayed as pseudo-chains in the balance
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    exchanges: Vec<Exchange>,
+    /// Whether entered commands are kept out of the encrypted command history
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    command_history_disabled: bool,
 }
 
 impl Display for ReplConfig {
@@ -60,18 +173,21 @@ pub struct Repl {
     chains: Vec<Chain>,
     config: ReplConfig,
     secret: Option<SecretString>,
+    /// Password given non-interactively, used instead of prompting for it
+    given_password: Option<SecretString>,
+    output: OutputFormat,
+    /// Entered commands, persisted after each one
+    command_history: Vec<String>,
+    /// Thread writing the command history, as encrypting it takes a while
+    command_history_task: Option<JoinHandle<()>>,
+    /// Whether config changes are saved once the script ends, as encrypting takes a while
+    is_running_script: bool,
+    has_unsaved_config: bool,
+    /// Whether an exit line stopped the running scripts
+    is_script_exited: bool,
     spinner: Spinner,
 }
 
-#[derive(Debug, Clone)]
-struct ReplBalanceEntry {
-    chain: String,
-    account: String,
-    token: Token,
-    balance_native: BigUint,
-    balance_usd: f64,
-}
-
 impl Repl {
     fn is_chain_enabled(&self, chain: &Chain) -> bool {
         let chain_id = chain.properties.get_id();
@@ -130,6 +246,13 @@ impl Repl {
             )),
         }
     }
+    /// Returns none for pseudo-chains, such as manual assets and exchanges
+    fn get_chain_by_name(&self, chain_name: &str) -> Option<&Chain> {
+        self.chains.iter().find(|c| c.properties.name == chain_name)
+    }
+    fn find_chain_by_name(&self, chain_name: &str) -> &Chain {
+        self.get_chain_by_name(chain_name).unwrap()
+    }
     fn find_account_address(&self, account: &str) -> Result<(&ChainType, &String), String> {
         match self
             .config
@@ -167,35 +290,18 @@ impl Repl {
         Err("Bad argument count".to_string())
     }
     fn display_help() {
-        let help = r###"
-chain - Display available chain-types and chains
-    chain [chain] - Show chain information
-    chain set [chain] [url] - Modify chain RPC url
-    chain rm [chain] - Remove custom chain RPC url
-    chain toggle [chain] - Toggle chain
-    chain toggle-all [chain-type] - Toggle all chains of chain-type
-account - Display accounts
-    account add [chain-type] [address] [alias?] - Add new address to track, optionally pass an alias
-    account rm [account] - Remove account
-token - Display tokens
-    token add [chain] [address] - Add new token
-    token rm [chain] [address] - Remove token
-    token scan [chain] [account] - Automatically scan account and add tokens
-balance - Display global balance
-config - Export BoP config in plain text
-    config password - Change password
-"###
-        .trim()
-        .lines()
-        .map(|line| {
-            let (command, description) = match line.split_once(" - ") {
-                Some(x) => x,
-                None => return line.to_string(),
-            };
-            format!("{} - {description}", command.to_colored())
-        })
-        .collect::<Vec<_>>()
-        .join("\n");
+        let help = HELP
+            .trim()
+            .lines()
+            .map(|line| {
+                let (command, description) = match line.split_once(" - ") {
+                    Some(x) => x,
+                    None => return line.to_string(),
+                };
+                format!("{} - {description}", command.to_colored())
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
         println!("{}\n{help}", "Commands".to_title());
     }
     fn handle_config(&mut self, command_parts: &[&str]) -> Result<(), String> {
@@ -207,20 +313,45 @@ config - Export BoP config in plain text
                 };
                 Ok(())
             }
-            _ => {
-                if command_parts[0] == "password" {
+            _ => match command_parts[0] {
+                "password" => {
+                    let history = self.read_history()?;
+                    let transactions = self.read_transactions()?;
                     self.create_password()?;
                     self.store_config_to_data_file()?;
+                    // History, transactions and commands are encrypted with the same password
+                    self.store_history(&history)?;
+                    self.store_transactions(&transactions)?;
+                    self.store_command_history();
                     println!("Password altered successfully");
-                    return Ok(());
+                    Ok(())
                 }
-                Self::get_unknown_option_err(command_parts[0])
-            }
+                "toggle-history" => self.toggle_command_history(),
+                x => Self::get_unknown_option_err(x),
+            },
         }
     }
+    fn chain_to_json(&self, chain: &Chain) -> serde_json::Value {
+        json!({
+            "id": chain.properties.get_id(),
+            "name": chain.properties.name,
+            "chain_type": chain.chain_type.to_string(),
+            "enabled": self.is_chain_enabled(chain),
+            "rpc_urls": chain.properties.rpc_urls.iter().map(|u| u.to_string()).collect::<Vec<_>>(),
+            "native_token": chain.properties.native_token,
+        })
+    }
     fn handle_chain(&mut self, command_parts: &[&str]) -> Result<(), String> {
         match command_parts.len() {
             0 => {
+                if self.is_json_output() {
+                    let chains = self
+                        .chains
+                        .iter()
+                        .map(|c| self.chain_to_json(c))
+                        .collect::<Vec<_>>();
+                    return Self::print_json(&chains);
+                }
                 let available_chain_types = format!(
                     "{} currently supports the following chain-types: {}, {}, {}",
                     BOOK_OF_PROFITS.to_colored(),
@@ -261,6 +392,9 @@ can use the same command to set an authentication token for the API.
             }
             1 => {
                 let chain = self.find_chain(command_parts[0])?;
+                if self.is_json_output() {
+                    return Self::print_json(&self.chain_to_json(chain));
+                }
                 println!(
                     "{} - {}",
                     chain.properties,
@@ -270,6 +404,9 @@ can use the same command to set an authentication token for the API.
                         "DISABLED".to_string()
                     }
                 );
+                if let Some(chain_id) = chain.properties.eip1191_chain_id {
+                    println!("Address checksums: EIP-1191 with chain id {chain_id}");
+                }
                 println!("Main RPC: {}", chain.properties.rpc_urls[0]);
                 println!(
                     "Fallback RPCs: [{}]",
@@ -327,32 +464,83 @@ can use the same command to set an authentication token for the API.
                         );
                         Ok(())
                     }
-                    _ => Self::get_unknown_option_expecting_or_err(&["rm", "toggle", "toggle-all"]),
+                    "checksum" => self.set_eip1191_chain_id(arg, None),
+                    _ => Self::get_unknown_option_expecting_or_err(&[
+                        "rm",
+                        "toggle",
+                        "toggle-all",
+                        "checksum",
+                    ]),
                 }
             }
             3 => {
                 let sub_command = command_parts[0];
-                if sub_command != "set" {
-                    return Repl::get_unknown_option_expecting_err("set");
-                }
                 let chain_id = command_parts[1];
                 let arg = command_parts[2];
-                let chain = self.find_chain(chain_id)?;
-                if chain.chain_type != ChainType::Ton && Url::from_str(arg).is_err() {
-                    return Err(format!("{arg:?} is not a valid url"));
+                match sub_command {
+                    "set" => {
+                        let chain = self.find_chain(chain_id)?;
+                        if chain.chain_type != ChainType::Ton && Url::from_str(arg).is_err() {
+                            return Err(format!("{arg:?} is not a valid url"));
+                        }
+                        self.config
+                            .rpcs
+                            .insert(chain_id.to_string(), arg.to_string());
+                        self.store_config_to_data_file()?;
+                        Ok(())
+                    }
+                    "checksum" => match arg.parse::<u64>() {
+                        Ok(x) => self.set_eip1191_chain_id(chain_id, Some(x)),
+                        _ => Err(format!("{arg:?} is not a valid chain id")),
+                    },
+                    _ => Self::get_unknown_option_expecting_or_err(&["set", "checksum"]),
                 }
-                self.config
-                    .rpcs
-                    .insert(chain_id.to_string(), arg.to_string());
-                self.store_config_to_data_file()?;
-                Ok(())
             }
             _ => Self::get_bad_argument_count_err(),
         }
     }
+    /// Sets the chain id EVM addresses of the chain are checksummed with, or goes back to
+    /// EIP-55 checksums when none is given
+    fn set_eip1191_chain_id(&mut self, chain: &str, chain_id: Option<u64>) -> Result<(), String> {
+        let chain = self.find_chain(chain)?;
+        if chain.chain_type != ChainType::Evm {
+            return Err(format!(
+                "{} addresses have no checksum to set",
+                chain.properties.name
+            ));
+        }
+        let (id, chain_name) = (chain.properties.get_id(), chain.properties.name.clone());
+        match chain_id {
+            Some(x) => self.config.eip1191_chain_ids.insert(id, x),
+            None => self.config.eip1191_chain_ids.remove(&id),
+        };
+        self.store_config_to_data_file()?;
+        match chain_id {
+            Some(x) => {
+                println!("{chain_name} addresses checksummed following EIP-1191 with chain id {x}")
+            }
+            None => println!("{chain_name} addresses checksummed following EIP-55"),
+        }
+        Ok(())
+    }
     fn handle_account(&mut self, command_parts: &[&str]) -> Result<(), String> {
         match command_parts.len() {
             0 => {
+                if self.is_json_output() {
+                    let accounts = self
+                        .config
+                        .accounts
+                        .iter()
+                        .map(|(chain_type, address, alias)| {
+                            json!({
+                                "chain_type": chain_type.to_string(),
+                                "address": address,
+                                "alias": alias,
+                            })
+                        })
+                        .collect::<Vec<_>>();
+                    return Self::print_json(&accounts);
+                }
                 let note = r###"
 To call a command involving an account, you can use either its full address or 
 alias, if set.
@@ -414,16 +602,25 @@ alias, if set.
                 }
                 let chain_type = ChainType::from_str(command_parts[1])?;
                 let addr = command_parts[2];
-                let address = match self
-                    .chains_of_type(&chain_type)
-                    .next()
-                    .unwrap()
-                    .parse_wallet_address(addr)
-                {
-                    Some(x) => x,
-                    None => {
+                // Chains of the same type may checksum addresses differently (EIP-1191), so
+                // the address is accepted as long as one of them validates it
+                let validation = {
+                    let mut chains_of_type = self.chains_of_type(&chain_type);
+                    chains_of_type
+                        .next()
+                        .unwrap()
+                        .validate_wallet_address(addr)
+                        .or_else(|err| {
+                            chains_of_type
+                                .find_map(|c| c.validate_wallet_address(addr).ok())
+                                .ok_or(err)
+                        })
+                };
+                let address = match validation {
+                    Ok(x) => x,
+                    Err(err) => {
                         return Err(format!(
-                            "{addr} is not a valid {} address",
+                            "{addr} is not a valid {} address: {err}",
                             chain_type.label()
                         ))
                     }
@@ -439,6 +636,15 @@ alias, if set.
     async fn handle_token(&mut self, command_parts: &[&str]) -> Result<(), String> {
         match command_parts.len() {
             0 => {
+                if self.is_json_output() {
+                    let tokens = self
+                        .config
+                        .tokens
+                        .iter()
+                        .map(|(chain_id, token)| json!({ "chain": chain_id, "token": token }))
+                        .collect::<Vec<_>>();
+                    return Self::print_json(&tokens);
+                }
                 if self.config.tokens.is_empty() {
                     println!("You have no tokens");
                 }
@@ -446,7 +652,12 @@ alias, if set.
                     let mut tokens = self
                         .tokens_of_chain(chain)
                         .map(|(_, t)| {
-                            Vec::from([t.symbol.clone(), t.address.clone(), t.decimals.to_string()])
+                            Vec::from([
+                                t.symbol.clone(),
+                                t.address.clone(),
+                                t.decimals.to_string(),
+                                t.kind.label(),
+                            ])
                         })
                         .collect::<Vec<_>>();
                     if tokens.is_empty() {
@@ -458,6 +669,7 @@ alias, if set.
                             "Symbol".to_string(),
                             "Address".to_string(),
                             "Decimals".to_string(),
+                            "Kind".to_string(),
                         ]),
                     );
                     let mut t = Table::from(tokens);
@@ -561,252 +773,51 @@ alias, if set.
             _ => Repl::get_bad_argument_count_err(),
         }
     }
-    async fn handle_balance(&mut self, command_parts: &[&str]) -> Result<(), String> {
-        match command_parts.len() {
-            0 => {
-                // TODO: Remove the partition part by making use of the `SupportOption`
-                // and stream everything together
-
-                // Partition between the accounts that support `get_holdings_balance` and those
-                // that do not
-                let (accounts_supported, accounts_not_supported): (Vec<_>, Vec<_>) = self
-                    .config
-                    .accounts
-                    .iter()
-                    .flat_map(|(chain_type, address, alias)| {
-                        self.enabled_chains_of_type(chain_type)
-                            .map(move |chain| (chain, address, alias))
-                    })
-                    .partition(|(chain, _, _)| chain.chain_type == ChainType::Ton);
-
-                let accounts_not_supported = accounts_not_supported
-                    .iter()
-                    .flat_map(|(chain, address, alias)| {
-                        self.tokens_of_chain(chain)
-                            .map(move |(_, token)| (chain, token.clone(), address, alias))
-                    })
-                    .collect::<Vec<_>>();
-
-                let accounts_natives = self
-                    .enabled_chains()
-                    .flat_map(|chain| {
-                        self.accounts_of_type(&chain.chain_type)
-                            .map(move |(_, address, alias)| (chain, address, alias))
-                    })
-                    .collect::<Vec<_>>();
-
-                let total_balances = accounts_supported.len()
-                    + accounts_not_supported.len()
-                    + accounts_natives.len();
-
-                self.spinner.set_total(total_balances);
-                self.spinner.start(Some("Querying balances..."));
-
-                let mut balances: Vec<ReplBalanceEntry> = Vec::new();
-
-                let results_natives = stream::iter(accounts_natives.iter().enumerate())
-                    .map(async |(i, (chain, address, _))| {
-                        let task = |rpc_index| chain.get_native_token_balance(address, rpc_index);
-                        let result = handle_retry_indexed(i, task).await;
-                        self.spinner.inc_progress();
-                        self.spinner
-                            .set_desc(Some(format!("({})", chain.properties.name)));
-                        result
-                    })
-                    .buffer_unordered(20)
-                    .collect::<Vec<_>>()
-                    .await;
-
-                let results_not_supported = stream::iter(accounts_not_supported.iter().enumerate())
-                    .map(async |(i, (chain, token, address, _))| {
-                        let task = |rpc_index| chain.get_token_balance(token, address, rpc_index);
-                        let result = handle_retry_indexed(i, task).await;
-                        self.spinner.inc_progress();
-                        self.spinner
-                            .set_desc(Some(format!("({})", chain.properties.name)));
-                        result
-                    })
-                    .buffer_unordered(20)
-                    .collect::<Vec<_>>()
-                    .await;
-
-                let results_supported = stream::iter(accounts_supported.iter().enumerate())
-                    .map(async |(i, (chain, address, _))| {
-                        let task = async |rpc_index| {
-                            (
-                                chain
-                                    .get_holdings_balance(address, rpc_index)
-                                    .await
-                                    .to_result()
-                                    .unwrap(),
-                                None,
-                            )
-                        };
-                        let result = handle_retry_indexed(i, task).await;
-                        self.spinner.inc_progress();
-                        self.spinner
-                            .set_desc(Some(format!("({})", chain.properties.name)));
-                        result
-                    })
-                    .buffer_unordered(20)
-                    .collect::<Vec<_>>()
-                    .await;
-
-                self.spinner.stop();
-
-                balances.extend(results_natives.iter().filter_map(|(i, balance)| {
-                    let (chain, address, alias) = &accounts_natives[*i];
-                    let account_label = Repl::format_account(address, alias);
-                    (*balance != BigUint::ZERO).then(|| ReplBalanceEntry {
-                        account: account_label.clone(),
-                        chain: chain.properties.name.clone(),
-                        token: chain.properties.native_token.clone(),
-                        balance_native: balance.clone(),
-                        balance_usd: 0.0,
-                    })
-                }));
-
-                balances.extend(results_not_supported.iter().filter_map(|(i, balance)| {
-                    let (chain, token, address, alias) = &accounts_not_supported[*i];
-                    let account_label = Repl::format_account(address, alias);
-                    (*balance != BigUint::ZERO).then(|| ReplBalanceEntry {
-                        account: account_label.clone(),
-                        chain: chain.properties.name.clone(),
-                        token: token.clone(),
-                        balance_native: balance.clone(),
-                        balance_usd: 0.0,
-                    })
-                }));
-
-                balances.extend(results_supported.iter().flat_map(|(i, account_holdings)| {
-                    let (chain, address, alias) = &accounts_supported[*i];
-                    let account_label = Repl::format_account(address, alias);
-                    let mut tokens_of_chain = self.tokens_of_chain(chain);
-                    account_holdings
-                        .iter()
-                        .filter_map(move |(token_address, balance)| {
-                            let (_, token) =
-                                tokens_of_chain.find(|(_, t)| t.address == *token_address)?;
-                            (*balance != BigUint::ZERO).then(|| ReplBalanceEntry {
-                                account: account_label.clone(),
-                                chain: chain.properties.name.clone(),
-                                token: token.clone(),
-                                balance_native: balance.clone(),
-                                balance_usd: 0.0,
-                            })
-                        })
-                }));
-
-                let tokens_to_fetch_price = balances
-                    .iter()
-                    .map(|b| b.token.address.as_str())
-                    .unique()
-                    .collect::<Vec<_>>();
-
-                self.spinner.set_total(tokens_to_fetch_price.len());
-                self.spinner.start(Some("Fetching token prices..."));
-
-                let stables = self
-                    .chains
-                    .iter()
-                    .flat_map(|c| &c.properties.stables)
-                    .map(|stable| stable.address.as_str())
-                    .collect::<Vec<_>>();
-
-                let pairs = match dexscreener::pairs::get_pairs_with_progress(
-                    tokens_to_fetch_price,
-                    stables,
-                    Some(|| {
-                        self.spinner.inc_progress();
-                    }),
-                )
-                .await
-                {
-                    Some(x) => x,
-                    None => return Err("Could not fetch tokens price".to_string()),
-                }
-                .iter()
-                .filter_map(|p| {
-                    let price: f64 = p.price_usd.clone()?.parse().ok()?;
-                    Some((p.base_token.address.clone(), price))
-                })
-                .collect::<Vec<_>>();
-
-                self.spinner.stop();
-
-                for balance in &mut balances {
-                    if let Some((_, price)) =
-                        pairs.iter().find(|pair| pair.0 == balance.token.address)
-                    {
-                        balance.balance_usd = price * balance.token.format(&balance.balance_native);
-                    }
-                }
-                balances.sort_by(|a, b| b.balance_usd.total_cmp(&a.balance_usd));
-                let relevant_balances = balances
-                    .iter()
-                    .filter(|balance| balance.balance_usd >= 0.01)
-                    .collect::<Vec<_>>();
-                let mut rows = relevant_balances
-                    .iter()
-                    .map(|balance| {
-                        Vec::from([
-                            balance.account.clone(),
-                            balance.chain.clone(),
-                            balance.token.symbol.clone(),
-                            balance.token.format(&balance.balance_native).to_string(),
-                            balance.balance_usd.round_to_fixed_string(2),
-                        ])
-                    })
-                    .collect::<Vec<_>>();
-                rows.insert(
-                    0,
-                    Vec::from([
-                        "Account".to_string(),
-                        "Chain".to_string(),
-                        "Token".to_string(),
-                        "Balance".to_string(),
-                        "Balance (USD)".to_string(),
-                    ]),
-                );
-                let mut t = Table::from(rows);
-                t.title = "Balances".to_string();
-                println!("{t}");
-                println!(
-                    "Holdings: {}\nBalance: {} USD",
-                    relevant_balances.len(),
-                    relevant_balances
-                        .iter()
-                        .fold(0.0, |sum, b| sum + b.balance_usd)
-                        .round_to_fixed_string(2),
-                );
-                Ok(())
-            }
-            _ => Repl::get_bad_argument_count_err(),
-        }
+    #[cfg(not(feature = "tui"))]
+    async fn handle_tui(&mut self, _command_parts: &[&str]) -> Result<(), String> {
+        Err("The dashboard requires installing with `--features tui`".to_string())
     }
-    async fn handle_command(&mut self, command: &str) {
+    async fn handle_command(&mut self, command: &str) -> Result<(), String> {
         if command.trim() == "" {
-            return;
+            return Ok(());
         }
         let command = command.split_whitespace().collect::<Vec<_>>();
         let command_parts = &command[1..];
-        if let Err(x) = match command[0] {
+        match command[0] {
             "balance" => self.handle_balance(command_parts).await,
             "token" => self.handle_token(command_parts).await,
+            "nft" => self.handle_nft(command_parts).await,
+            "history" => self.handle_history(command_parts),
+            "rebalance" => self.handle_rebalance(command_parts).await,
+            "cost" => self.handle_cost(command_parts),
+            "asset" => self.handle_asset(command_parts),
+            "exchange" => self.handle_exchange(command_parts),
+            "tx" => self.handle_transactions(command_parts).await,
+            "report" => self.handle_report(command_parts),
             "chain" => self.handle_chain(command_parts),
             "account" => self.handle_account(command_parts),
             "config" => self.handle_config(command_parts),
+            "set" => self.handle_set(command_parts),
+            "source" => self.handle_source(command_parts).await,
+            "tui" => self.handle_tui(command_parts).await,
             "help" | "?" => {
                 Self::display_help();
                 Ok(())
             }
+            // Scripts are only stopped, so that their config changes are still saved
+            "exit" | "quit" if self.is_running_script => {
+                self.is_script_exited = true;
+                Ok(())
+            }
             "exit" | "quit" => std::process::exit(0),
             x => Err(format!("Unknown command: {x:?}")),
-        } {
-            eprintln!("{x}");
         }
     }
     fn create_password(&mut self) -> Result<(), String> {
+        if let Some(password) = self.given_password.take() {
+            self.secret = (!password.expose_secret().is_empty()).then_some(password);
+            return Ok(());
+        }
         let secret = match age::cli_common::read_secret(
             "Create password (leave empty if you don't require encryption)",
             "Password",
@@ -824,6 +835,10 @@ alias, if set.
         Ok(())
     }
     fn read_password(&mut self) -> Result<(), String> {
+        if let Some(password) = self.given_password.take() {
+            self.secret = Some(password);
+            return Ok(());
+        }
         let pass = match age::cli_common::read_secret(
             "Enter password",
             format!("{BOOK_OF_PROFITS} password").as_str(),
@@ -845,12 +860,10 @@ alias, if set.
                 contents = match age::decrypt(&identity, data.as_slice()) {
                     Ok(x) => Some(x),
                     _ => {
-                        let err = "Bad password, try again".to_string();
-                        if keep_trying {
-                            eprintln!("{err}");
-                        } else {
-                            return Err(err);
+                        if !keep_trying {
+                            return Err("Bad password".to_string());
                         }
+                        eprintln!("Bad password, try again");
                         continue;
                     }
                 };
@@ -867,6 +880,13 @@ alias, if set.
         }
     }
     fn sync_rpcs(&mut self) {
+        for chain in &mut self.chains {
+            chain.properties.eip1191_chain_id = self
+                .config
+                .eip1191_chain_ids
+                .get(&chain.properties.get_id())
+                .copied();
+        }
         let default_chains = Self::default().chains;
         let _ = self
             .chains
@@ -907,32 +927,100 @@ alias, if set.
             })
             .collect::<Vec<_>>();
     }
-    fn store_config_to_data_file(&mut self) -> Result<(), String> {
-        let mut contents = serde_json::to_vec(&self.config).unwrap();
-        if self.secret.is_some() {
-            let recipient = age::scrypt::Recipient::new(self.secret.clone().unwrap());
-            let encrypted_contents = match age::encrypt(&recipient, contents.as_slice()) {
-                Ok(x) => x,
-                _ => return Err("Could not encrypt config".to_string()),
-            };
-            contents = encrypted_contents;
+    fn encrypt(&self, contents: Vec<u8>) -> Result<Vec<u8>, String> {
+        Self::encrypt_with(self.secret.as_ref(), contents)
+    }
+    /// Encrypts data with the given password, for when the REPL is not at hand
+    fn encrypt_with(secret: Option<&SecretString>, contents: Vec<u8>) -> Result<Vec<u8>, String> {
+        match secret {
+            Some(secret) => {
+                let recipient = age::scrypt::Recipient::new(secret.clone());
+                match age::encrypt(&recipient, contents.as_slice()) {
+                    Ok(x) => Ok(x),
+                    _ => Err("Could not encrypt data".to_string()),
+                }
+            }
+            None => Ok(contents),
+        }
+    }
+    /// Decrypts data with the password that was entered at startup
+    fn decrypt(&self, data: Vec<u8>) -> Result<Vec<u8>, String> {
+        if age::Decryptor::new(data.as_slice()).is_err() {
+            return Ok(data);
+        }
+        let secret = match &self.secret {
+            Some(x) => x.clone(),
+            None => return Err("Data is encrypted but no password was set".to_string()),
         };
+        match age::decrypt(&age::scrypt::Identity::new(secret), data.as_slice()) {
+            Ok(x) => Ok(x),
+            _ => Err("Could not decrypt data".to_string()),
+        }
+    }
+    /// Reads a file stored alongside the data file, encrypted with the same password
+    fn read_encrypted_file<T: DeserializeOwned + Default>(&self, file: &str) -> Result<T, String> {
+        if !file_exists(file)? {
+            return Ok(T::default());
+        }
+        let contents = self.decrypt(read_file(file)?)?;
+        match serde_json::from_slice::<T>(contents.as_slice()) {
+            Ok(x) => Ok(x),
+            _ => Err(format!("Bad contents in {file}")),
+        }
+    }
+    fn store_encrypted_file<T: Serialize>(&self, file: &str, value: &T) -> Result<(), String> {
+        let contents = self.encrypt(serde_json::to_vec(value).unwrap())?;
+        write_file(file, contents.as_slice())
+    }
+    fn store_config_to_data_file(&mut self) -> Result<(), String> {
+        if self.is_running_script {
+            self.has_unsaved_config = true;
+            self.sync_rpcs();
+            return Ok(());
+        }
+        self.has_unsaved_config = false;
+        let contents = self.encrypt(serde_json::to_vec(&self.config).unwrap())?;
         write_data_file(contents.as_slice())?;
         self.sync_rpcs();
         Ok(())
     }
+    /// Skips the password prompt, e.g. when running from a script
+    pub fn with_password(mut self, password: SecretString) -> Self {
+        self.given_password = Some(password);
+        self
+    }
     fn startup_config(&mut self) -> Result<(), String> {
         if !data_file_exists()? {
             self.create_password()?;
             return self.store_config_to_data_file();
         }
-        self.config = self.read_config_from_data_file(true)?;
+        // A given password is only tried once
+        let keep_trying = self.given_password.is_none();
+        self.config = self.read_config_from_data_file(keep_trying)?;
         self.sync_rpcs();
+        self.command_history = self.read_command_history()?;
         Ok(())
     }
+    /// Runs a single command, as if it was entered in the REPL
+    pub async fn run_command(&mut self, command: &str) -> Result<(), String> {
+        self.startup_config()?;
+        let result = self.handle_command(command).await;
+        self.wait_command_history_stored();
+        result
+    }
+    /// Runs the commands of a script file, as if they were sourced in the REPL
+    pub async fn run_file(&mut self, path: &str) -> Result<(), String> {
+        self.startup_config()?;
+        let result = self.run_script(path).await;
+        self.wait_command_history_stored();
+        result
+    }
     pub async fn run(&mut self) -> Result<(), String> {
         self.startup_config()?;
-        let mut rl = DefaultEditor::new().unwrap();
+        let mut rl = Editor::<ReplHelper, DefaultHistory>::new().unwrap();
+        for command in &self.command_history {
+            rl.add_history_entry(command).unwrap();
+        }
         let mut last_command: Option<String> = None;
         let mut interrupted = false;
         println!(
@@ -940,6 +1028,8 @@ alias, if set.
             BOOK_OF_PROFITS.to_colored()
         );
         loop {
+            // Refreshed as commands may add chains, accounts or tokens to complete
+            rl.set_helper(Some(self.create_helper()));
             match rl.readline("> ".to_colored().as_str()) {
                 Ok(line) => {
                     if interrupted {
@@ -952,22 +1042,44 @@ alias, if set.
                     } else {
                         last_command = Some(line.clone());
                     }
-                    self.handle_command(command).await;
-                    rl.add_history_entry(command).unwrap();
+                    if command == "exit" || command == "quit" {
+                        break;
+                    }
+                    // Stored before running the command, so that it is kept if the REPL is
+                    // killed while running it
+                    let is_recorded = !command.is_empty() && !Self::has_secret(command);
+                    if is_recorded {
+                        self.command_history.push(command.to_string());
+                        self.store_command_history();
+                    }
+                    let command_count = self.command_history.len();
+                    if let Err(err) = self.handle_command(command).await {
+                        eprintln!("{err}");
+                    }
+                    if self.command_history.len() < command_count {
+                        // Cleared by the command
+                        rl.clear_history().unwrap();
+                        continue;
+                    }
+                    if is_recorded {
+                        rl.add_history_entry(command).unwrap();
+                    }
                 }
                 Err(ReadlineError::Interrupted) => {
                     if !interrupted {
                         interrupted = true;
                         println!("(Press ^C again to exit)");
                     } else {
-                        std::process::exit(0);
+                        break;
                     }
                 }
                 Err(ReadlineError::Eof) => {
-                    std::process::exit(0);
+                    break;
                 }
                 _ => {}
             }
         }
+        self.wait_command_history_stored();
+        Ok(())
     }
 }
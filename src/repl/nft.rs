@@ -0,0 +1,450 @@
+use std::str::FromStr;
+
+use futures::{stream, StreamExt};
+use itertools::Itertools;
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+
+use crate::{
+    chain::{
+        chain_type::ChainType,
+        nft::{NftCollection, NftStandard},
+        Chain, ChainOps,
+    },
+    dexscreener, opensea,
+    utils::{float::ExtendFloat, retry::handle_limited_retry_indexed, table::Table},
+};
+
+use super::Repl;
+
+/// Attempts at reading the balance of a collection, so that a failing RPC does not block
+/// the other collections
+static MAX_NFT_COUNT_ATTEMPTS: usize = 6;
+
+/// Tokens of a tracked collection owned by an account
+pub struct NftHolding {
+    pub account: String,
+    pub chain: String,
+    pub collection: NftCollection,
+    pub count: BigUint,
+    pub floor_usd: Option<f64>,
+}
+
+impl NftHolding {
+    pub fn value_usd(&self) -> Option<f64> {
+        Some(self.floor_usd? * self.count.to_f64()?)
+    }
+}
+
+impl Repl {
+    fn nfts_of_chain<'a>(
+        &'a self,
+        chain: &'a Chain,
+    ) -> impl Iterator<Item = &'a (String, NftCollection)> {
+        let chain_id = chain.properties.get_id();
+        self.config
+            .nfts
+            .iter()
+            .filter(move |(c_id, _)| *c_id == chain_id)
+    }
+    async fn get_floor_prices(&self, holdings: &[NftHolding]) -> Vec<Option<f64>> {
+        let api_key = self.config.opensea_api_key.clone();
+        let floors = stream::iter(holdings.iter())
+            .map(async |h| {
+                if h.collection.floor_usd.is_some() {
+                    return None;
+                }
+                let chain = self.find_chain_by_name(&h.chain);
+                opensea::collections::get_floor_price(
+                    api_key.as_deref()?,
+                    &chain.properties.get_id(),
+                    &h.collection.address,
+                )
+                .await
+            })
+            .buffered(5)
+            .collect::<Vec<_>>()
+            .await;
+        // Floor prices are usually quoted in the native token of the chain
+        let native_tokens = holdings
+            .iter()
+            .map(|h| &self.find_chain_by_name(&h.chain).properties.native_token)
+            .collect::<Vec<_>>();
+        let native_prices = dexscreener::pairs::get_pairs(
+            native_tokens.iter().map(|t| t.address.as_str()).collect(),
+            Vec::new(),
+        )
+        .await
+        .unwrap_or_default();
+        holdings
+            .iter()
+            .zip(floors)
+            .zip(native_tokens)
+            .map(|((h, floor), native_token)| {
+                if let Some(floor_usd) = h.collection.floor_usd {
+                    return Some(floor_usd);
+                }
+                let floor = floor?;
+                let symbol = floor.symbol.trim_start_matches('W');
+                if symbol != native_token.symbol.trim_start_matches('W') {
+                    return None;
+                }
+                let price: f64 = native_prices
+                    .iter()
+                    .find(|p| p.base_token.address == native_token.address)?
+                    .price_usd
+                    .clone()?
+                    .parse()
+                    .ok()?;
+                Some(floor.price * price)
+            })
+            .collect()
+    }
+    pub(super) async fn get_nft_holdings(&self) -> Vec<NftHolding> {
+        let queries = self
+            .config
+            .nfts
+            .iter()
+            .filter_map(|(chain_id, collection)| {
+                let chain = self.find_chain(chain_id).ok()?;
+                self.is_chain_enabled(chain).then_some((chain, collection))
+            })
+            .flat_map(|(chain, collection)| {
+                self.accounts_of_type(&chain.chain_type)
+                    .map(move |(_, address, alias)| (chain, collection, address, alias))
+            })
+            .collect::<Vec<_>>();
+
+        self.spinner.set_total(queries.len());
+        self.spinner.start(Some("Querying NFT balances..."));
+
+        let results = stream::iter(queries.iter().enumerate())
+            .map(async |(i, (chain, collection, address, _))| {
+                let task = |rpc_index| chain.get_nft_count(collection, address, rpc_index);
+                let (i, count) =
+                    handle_limited_retry_indexed(i, MAX_NFT_COUNT_ATTEMPTS, task).await;
+                let result = (
+                    i,
+                    count.unwrap_or_else(|| {
+                        eprintln!(
+                            "Could not fetch the {} balance of {address} on {}",
+                            collection.name, chain.properties.name
+                        );
+                        BigUint::ZERO
+                    }),
+                );
+                self.spinner.inc_progress();
+                self.spinner
+                    .set_desc(Some(format!("({})", chain.properties.name)));
+                result
+            })
+            .buffer_unordered(20)
+            .collect::<Vec<_>>()
+            .await;
+
+        self.spinner.stop();
+
+        let mut holdings = results
+            .into_iter()
+            .filter(|(_, count)| *count != BigUint::ZERO)
+            .map(|(i, count)| {
+                let (chain, collection, address, alias) = queries[i];
+                NftHolding {
+                    account: Repl::format_account(address, alias),
+                    chain: chain.properties.name.clone(),
+                    collection: collection.clone(),
+                    count,
+                    floor_usd: None,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        self.spinner.start(Some("Fetching floor prices..."));
+        let floors = self.get_floor_prices(&holdings).await;
+        self.spinner.stop();
+
+        for (holding, floor) in holdings.iter_mut().zip(floors) {
+            holding.floor_usd = floor;
+        }
+        holdings.sort_by(|a, b| {
+            b.value_usd()
+                .unwrap_or_default()
+                .total_cmp(&a.value_usd().unwrap_or_default())
+        });
+        holdings
+    }
+    fn find_nft_index(&self, chain: &Chain, address: &str) -> Result<usize, String> {
+        let chain_id = chain.properties.get_id();
+        let contract_address = match chain.parse_token_address(address) {
+            Some(x) => x,
+            None => {
+                return Err(format!(
+                    "{address} is not a valid {} contract address",
+                    chain.properties.name
+                ))
+            }
+        };
+        match self
+            .config
+            .nfts
+            .iter()
+            .position(|(c_id, c)| *c_id == chain_id && c.address == contract_address)
+        {
+            Some(x) => Ok(x),
+            None => Err(format!(
+                "Could not find collection with address {contract_address:?}"
+            )),
+        }
+    }
+    pub(super) async fn handle_nft(&mut self, command_parts: &[&str]) -> Result<(), String> {
+        match command_parts.len() {
+            0 => {
+                if self.config.nfts.is_empty() {
+                    println!("You have no NFT collections");
+                }
+                for chain in &self.chains {
+                    let mut rows = self
+                        .nfts_of_chain(chain)
+                        .map(|(_, c)| {
+                            Vec::from([
+                                c.name.clone(),
+                                c.address.clone(),
+                                c.standard.label(),
+                                if c.token_ids.is_empty() {
+                                    "-".to_string()
+                                } else {
+                                    c.token_ids.join(",")
+                                },
+                                c.floor_usd
+                                    .map(|f| f.round_to_fixed_string(2))
+                                    .unwrap_or("-".to_string()),
+                            ])
+                        })
+                        .collect::<Vec<_>>();
+                    if rows.is_empty() {
+                        continue;
+                    }
+                    rows.insert(
+                        0,
+                        Vec::from([
+                            "Name".to_string(),
+                            "Address".to_string(),
+                            "Standard".to_string(),
+                            "Token IDs".to_string(),
+                            "Fixed floor (USD)".to_string(),
+                        ]),
+                    );
+                    let mut t = Table::from(rows);
+                    t.title = format!("{} NFT collections", chain.properties.name);
+                    println!("{t}");
+                }
+                println!(
+                    "NFTs are {} in the balance",
+                    if self.config.nfts_in_balance {
+                        "included"
+                    } else {
+                        "not included"
+                    }
+                );
+                Ok(())
+            }
+            1 => match command_parts[0] {
+                "balance" => {
+                    let holdings = self.get_nft_holdings().await;
+                    let mut rows = holdings
+                        .iter()
+                        .map(|h| {
+                            Vec::from([
+                                h.account.clone(),
+                                h.chain.clone(),
+                                h.collection.name.clone(),
+                                h.count.to_string(),
+                                h.floor_usd
+                                    .map(|f| f.round_to_fixed_string(2))
+                                    .unwrap_or("-".to_string()),
+                                h.value_usd()
+                                    .map(|v| v.round_to_fixed_string(2))
+                                    .unwrap_or("-".to_string()),
+                            ])
+                        })
+                        .collect::<Vec<_>>();
+                    rows.insert(
+                        0,
+                        Vec::from([
+                            "Account".to_string(),
+                            "Chain".to_string(),
+                            "Collection".to_string(),
+                            "Owned".to_string(),
+                            "Floor (USD)".to_string(),
+                            "Value (USD)".to_string(),
+                        ]),
+                    );
+                    let mut t = Table::from(rows);
+                    t.title = "NFTs".to_string();
+                    println!("{t}");
+                    println!(
+                        "Holdings: {}\nValue: {} USD",
+                        holdings.len(),
+                        holdings
+                            .iter()
+                            .filter_map(|h| h.value_usd())
+                            .sum::<f64>()
+                            .round_to_fixed_string(2)
+                    );
+                    if self.config.opensea_api_key.is_none() {
+                        println!("Set an OpenSea API key with `nft key` to fetch floor prices");
+                    }
+                    Ok(())
+                }
+                "toggle-balance" => {
+                    self.config.nfts_in_balance = !self.config.nfts_in_balance;
+                    self.store_config_to_data_file()?;
+                    println!(
+                        "NFTs {} in the balance",
+                        if self.config.nfts_in_balance {
+                            "included"
+                        } else {
+                            "excluded"
+                        }
+                    );
+                    Ok(())
+                }
+                _ => Repl::get_unknown_option_expecting_or_err(&["balance", "toggle-balance"]),
+            },
+            2 => {
+                if command_parts[0] != "key" {
+                    return Repl::get_unknown_option_expecting_err("key");
+                }
+                self.config.opensea_api_key = Some(command_parts[1].to_string());
+                self.store_config_to_data_file()
+            }
+            3 | 4 => {
+                let sub_command = command_parts[0];
+                let chain_id = command_parts[1];
+                let chain = self.find_chain(chain_id)?;
+                let addr = command_parts[2];
+                match (sub_command, command_parts.len()) {
+                    ("add", _) => {
+                        if chain.chain_type != ChainType::Evm {
+                            return Err(
+                                "NFT collections can only be tracked on EVM chains".to_string()
+                            );
+                        }
+                        let contract_address = match chain.parse_token_address(addr) {
+                            Some(x) => x,
+                            None => {
+                                return Err(format!(
+                                    "{addr} is not a valid {} contract address",
+                                    chain.properties.name
+                                ))
+                            }
+                        };
+                        if self.find_nft_index(chain, &contract_address).is_ok() {
+                            return Err("Collection already added".to_string());
+                        }
+                        let token_ids = match command_parts.get(3) {
+                            Some(ids) => ids
+                                .split(',')
+                                .map(|id| match BigUint::from_str(id) {
+                                    Ok(x) => Ok(x.to_string()),
+                                    Err(_) => Err(format!("{id:?} is not a valid token id")),
+                                })
+                                .collect::<Result<Vec<_>, _>>()?,
+                            None => Vec::new(),
+                        };
+                        let collection = match chain
+                            .get_nft_collection(&contract_address, token_ids, 0)
+                            .await
+                            .to_result()?
+                        {
+                            Some(x) => x,
+                            None => {
+                                return Err(
+                                    "Contract is neither an ERC-721 nor an ERC-1155 collection"
+                                        .to_string(),
+                                )
+                            }
+                        };
+                        if collection.token_ids.is_empty()
+                            && collection.standard == NftStandard::Erc1155
+                        {
+                            return Err("ERC-1155 collections require token ids".to_string());
+                        }
+                        println!(
+                            "Tracking {} ({})",
+                            collection.name,
+                            collection.standard.label()
+                        );
+                        self.config.nfts.push((chain_id.to_string(), collection));
+                        self.store_config_to_data_file()
+                    }
+                    ("list", 3) => {
+                        let (chain_type, account_address) = self.find_account_address(addr)?;
+                        if *chain_type != chain.chain_type {
+                            return Err(format!(
+                                "Account does not belong to the {} chain-type",
+                                chain.chain_type.label(),
+                            ));
+                        }
+                        self.spinner.start(Some("Listing NFTs..."));
+                        let nfts = chain.list_nfts(account_address, 0).await.to_result();
+                        self.spinner.stop();
+                        let nfts = match nfts? {
+                            Some(x) => x,
+                            None => return Err("Could not fetch account NFTs".to_string()),
+                        };
+                        if nfts.is_empty() {
+                            println!("Found no NFTs");
+                            return Ok(());
+                        }
+                        let mut rows = nfts
+                            .iter()
+                            .sorted_by(|a, b| a.collection.cmp(&b.collection))
+                            .map(|nft| {
+                                Vec::from([
+                                    nft.name.clone(),
+                                    nft.collection.clone().unwrap_or("-".to_string()),
+                                    nft.address.clone(),
+                                ])
+                            })
+                            .collect::<Vec<_>>();
+                        rows.insert(
+                            0,
+                            Vec::from([
+                                "Name".to_string(),
+                                "Collection".to_string(),
+                                "Address".to_string(),
+                            ]),
+                        );
+                        let mut t = Table::from(rows);
+                        t.title = format!("{} NFTs", chain.properties.name);
+                        println!("{t}");
+                        println!("NFTs: {}", nfts.len());
+                        Ok(())
+                    }
+                    ("rm", 3) => {
+                        let index = self.find_nft_index(chain, addr)?;
+                        self.config.nfts.remove(index);
+                        self.store_config_to_data_file()
+                    }
+                    ("floor", 4) => {
+                        let index = self.find_nft_index(chain, addr)?;
+                        let floor = match f64::from_str(command_parts[3]) {
+                            Ok(x) if x >= 0.0 => x,
+                            _ => {
+                                return Err(format!(
+                                    "{:?} is not a valid USD amount",
+                                    command_parts[3]
+                                ))
+                            }
+                        };
+                        self.config.nfts[index].1.floor_usd = Some(floor);
+                        self.store_config_to_data_file()
+                    }
+                    _ => Repl::get_unknown_option_expecting_or_err(&["add", "list", "rm", "floor"]),
+                }
+            }
+            _ => Repl::get_bad_argument_count_err(),
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use super::Repl;
+
+/// How commands display their results
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum OutputFormat {
+    /// Human-readable tables
+    #[default]
+    Table,
+    /// Structured JSON, for other programs to consume
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Self::Table),
+            "json" => Ok(Self::Json),
+            x => Err(format!(
+                "{x:?} is not a valid output, expecting table or json"
+            )),
+        }
+    }
+}
+
+impl Repl {
+    /// Switches the output format, hiding the spinner so it does not mix with JSON
+    pub fn with_output(mut self, output: OutputFormat) -> Self {
+        self.set_output(output);
+        self
+    }
+    fn set_output(&mut self, output: OutputFormat) {
+        self.output = output;
+        self.spinner.is_hidden = output == OutputFormat::Json;
+    }
+    pub(super) fn is_json_output(&self) -> bool {
+        self.output == OutputFormat::Json
+    }
+    pub(super) fn print_json<T: Serialize + ?Sized>(value: &T) -> Result<(), String> {
+        match serde_json::to_string(value) {
+            Ok(x) => {
+                println!("{x}");
+                Ok(())
+            }
+            _ => Err("Could not serialize output".to_string()),
+        }
+    }
+    pub(super) fn handle_set(&mut self, command_parts: &[&str]) -> Result<(), String> {
+        match command_parts.len() {
+            2 => match command_parts[0] {
+                "output" => {
+                    self.set_output(OutputFormat::from_str(command_parts[1])?);
+                    Ok(())
+                }
+                _ => Repl::get_unknown_option_expecting_err("output"),
+            },
+            _ => Repl::get_bad_argument_count_err(),
+        }
+    }
+}
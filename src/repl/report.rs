@@ -0,0 +1,382 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, NaiveDate};
+
+use crate::{
+    chain::token::Token,
+    utils::{
+        float::ExtendFloat,
+        table::{CsvRenderer, Table, TableRenderer},
+    },
+};
+
+use super::{cost::CostLot, transactions::Transaction, Repl};
+
+/// Order in which acquisition lots are matched against disposals
+#[derive(Debug, Clone, Copy)]
+pub enum LotMethod {
+    /// First in, first out
+    Fifo,
+    /// Last in, first out
+    Lifo,
+    /// Highest cost, first out
+    Hifo,
+}
+
+impl FromStr for LotMethod {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "fifo" => Ok(Self::Fifo),
+            "lifo" => Ok(Self::Lifo),
+            "hifo" => Ok(Self::Hifo),
+            x => Err(format!(
+                "{x:?} is not a valid method, expecting fifo, lifo or hifo"
+            )),
+        }
+    }
+}
+
+/// Part of a disposal matched against a single acquisition lot
+pub(super) struct RealizedGain {
+    /// Chain-id
+    chain: String,
+    token: Token,
+    amount: f64,
+    /// None when there were no lots left to match the disposal against
+    acquired: Option<NaiveDate>,
+    sold: NaiveDate,
+    proceeds: f64,
+    cost: f64,
+}
+
+impl RealizedGain {
+    fn gain(&self) -> f64 {
+        self.proceeds - self.cost
+    }
+    /// Assets held for more than a year are long-term
+    fn is_long_term(&self) -> bool {
+        self.acquired
+            .is_some_and(|acquired| (self.sold - acquired).num_days() > 365)
+    }
+}
+
+/// Matches the priced disposals of the transactions against the lots, taking the matched
+/// amounts out of the lots. Returns the realized gains and the number of disposals without a
+/// price
+pub(super) fn match_disposals(
+    lots: &mut [(String, CostLot)],
+    transactions: &[Transaction],
+    method: LotMethod,
+) -> (Vec<RealizedGain>, usize) {
+    let mut gains = Vec::new();
+    let mut unpriced = 0;
+    for disposal in transactions.iter().filter(|t| t.is_disposal()) {
+        let (price, sold) = match (
+            disposal.price,
+            DateTime::from_timestamp(disposal.timestamp, 0),
+        ) {
+            (Some(price), Some(sold)) => (price, sold.date_naive()),
+            _ => {
+                unpriced += 1;
+                continue;
+            }
+        };
+        let mut remaining = disposal.amount;
+        while remaining > 0.0 {
+            let candidates = lots.iter().enumerate().filter(|(_, (chain_id, lot))| {
+                *chain_id == disposal.chain
+                    && lot.token.address == disposal.token.address
+                    && lot.amount > 0.0
+                    && lot.date <= sold
+            });
+            let lot_index = match method {
+                LotMethod::Fifo => candidates.min_by_key(|(_, (_, lot))| lot.date),
+                LotMethod::Lifo => candidates.max_by_key(|(_, (_, lot))| lot.date),
+                LotMethod::Hifo => {
+                    candidates.max_by(|(_, (_, a)), (_, (_, b))| a.price.total_cmp(&b.price))
+                }
+            }
+            .map(|(i, _)| i);
+            let (amount, acquired, cost_price) = match lot_index {
+                Some(i) => {
+                    let lot = &mut lots[i].1;
+                    let amount = remaining.min(lot.amount);
+                    lot.amount -= amount;
+                    (amount, Some(lot.date), lot.price)
+                }
+                // The rest of the disposal has an unknown cost basis
+                None => (remaining, None, 0.0),
+            };
+            remaining -= amount;
+            gains.push(RealizedGain {
+                chain: disposal.chain.clone(),
+                token: disposal.token.clone(),
+                amount,
+                acquired,
+                sold,
+                proceeds: amount * price,
+                cost: amount * cost_price,
+            });
+        }
+    }
+    (gains, unpriced)
+}
+
+impl Repl {
+    /// Matches every priced disposal of the imported transactions against the cost basis
+    /// lots, returning the realized gains and the number of disposals without a price
+    fn get_realized_gains(&self, method: LotMethod) -> Result<(Vec<RealizedGain>, usize), String> {
+        let transactions = self.read_transactions()?;
+        let mut lots = self.get_cost_lots(&transactions);
+        Ok(match_disposals(&mut lots, &transactions, method))
+    }
+    fn display_gains_report(
+        &self,
+        year: i32,
+        method: LotMethod,
+        csv_path: Option<&str>,
+    ) -> Result<(), String> {
+        let (gains, unpriced) = self.get_realized_gains(method)?;
+        let gains = gains
+            .into_iter()
+            .filter(|g| g.sold.year() == year)
+            .collect::<Vec<_>>();
+        let mut rows = gains
+            .iter()
+            .map(|g| {
+                Vec::from([
+                    g.token.symbol.clone(),
+                    g.chain.clone(),
+                    g.amount.to_string(),
+                    g.acquired
+                        .map(|d| d.to_string())
+                        .unwrap_or("Unknown".to_string()),
+                    g.sold.to_string(),
+                    g.proceeds.round_to_fixed_string(2),
+                    g.cost.round_to_fixed_string(2),
+                    g.gain().round_to_fixed_string(2),
+                    if g.is_long_term() { "Long" } else { "Short" }.to_string(),
+                ])
+            })
+            .collect::<Vec<_>>();
+        rows.insert(
+            0,
+            Vec::from([
+                "Token".to_string(),
+                "Chain".to_string(),
+                "Amount".to_string(),
+                "Date acquired".to_string(),
+                "Date sold".to_string(),
+                "Proceeds (USD)".to_string(),
+                "Cost basis (USD)".to_string(),
+                "Gain (USD)".to_string(),
+                "Term".to_string(),
+            ]),
+        );
+        let mut t = Table::from(rows);
+        t.title = format!(
+            "Realized gains of {year} ({})",
+            format!("{method:?}").to_uppercase()
+        );
+        println!("{t}");
+        let (long_term, short_term): (Vec<_>, Vec<_>) =
+            gains.iter().partition(|g| g.is_long_term());
+        let sum = |gains: &[&RealizedGain]| gains.iter().fold(0.0, |sum, g| sum + g.gain());
+        println!(
+            "Short-term: {} USD\nLong-term: {} USD\nTotal: {} USD",
+            sum(&short_term).round_to_signed_string(2),
+            sum(&long_term).round_to_signed_string(2),
+            (sum(&short_term) + sum(&long_term)).round_to_signed_string(2),
+        );
+        if gains.iter().any(|g| g.acquired.is_none()) {
+            println!("Some disposals exceed the known lots, their cost basis is considered 0");
+        }
+        if unpriced > 0 {
+            println!("Skipped {unpriced} disposals without a historical price");
+        }
+        if let Some(path) = csv_path {
+            if std::fs::write(path, CsvRenderer.render(&t)).is_err() {
+                return Err(format!("Could not write {path}"));
+            }
+            println!("Report exported to {path}");
+        }
+        Ok(())
+    }
+    pub(super) fn handle_report(&mut self, command_parts: &[&str]) -> Result<(), String> {
+        match command_parts.len() {
+            3 | 4 => match command_parts[0] {
+                "gains" => {
+                    let year = match command_parts[1].parse::<i32>() {
+                        Ok(x) => x,
+                        _ => return Err("Invalid year".to_string()),
+                    };
+                    let method = LotMethod::from_str(command_parts[2])?;
+                    self.display_gains_report(year, method, command_parts.get(3).copied())
+                }
+                _ => Repl::get_unknown_option_expecting_err("gains"),
+            },
+            _ => Repl::get_bad_argument_count_err(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::{
+        chain::token::Token,
+        repl::{cost::CostLot, transactions::Transaction},
+    };
+
+    use super::{match_disposals, LotMethod};
+
+    fn get_token() -> Token {
+        Token::hardcode("ETH", "0x0", 18)
+    }
+
+    fn get_date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    fn get_lot(amount: f64, price: f64, day: u32) -> (String, CostLot) {
+        (
+            "eth".to_string(),
+            CostLot {
+                token: get_token(),
+                amount,
+                price,
+                date: get_date(day),
+            },
+        )
+    }
+
+    fn get_disposal(amount: f64, price: Option<f64>, day: u32) -> Transaction {
+        Transaction {
+            chain: "eth".to_string(),
+            account: "0x1".to_string(),
+            hash: format!("0x{day}"),
+            timestamp: get_date(day)
+                .and_hms_opt(12, 0, 0)
+                .unwrap()
+                .and_utc()
+                .timestamp(),
+            token: get_token(),
+            amount,
+            is_incoming: false,
+            is_internal: false,
+            price,
+        }
+    }
+
+    fn get_lots() -> Vec<(String, CostLot)> {
+        Vec::from([
+            get_lot(1.0, 100.0, 1),
+            get_lot(1.0, 300.0, 2),
+            get_lot(1.0, 200.0, 3),
+        ])
+    }
+
+    fn get_remaining(lots: &[(String, CostLot)]) -> Vec<f64> {
+        lots.iter().map(|(_, lot)| lot.amount).collect()
+    }
+
+    #[test]
+    fn fifo_matches_oldest_lots() {
+        let mut lots = get_lots();
+        let (gains, unpriced) = match_disposals(
+            &mut lots,
+            &[get_disposal(1.5, Some(400.0), 10)],
+            LotMethod::Fifo,
+        );
+        assert_eq!(unpriced, 0);
+        assert_eq!(gains.len(), 2);
+        assert_eq!(gains[0].acquired, Some(get_date(1)));
+        assert_eq!(gains[0].cost, 100.0);
+        assert_eq!(gains[1].acquired, Some(get_date(2)));
+        assert_eq!(gains[1].amount, 0.5);
+        assert_eq!(gains[1].cost, 150.0);
+        assert_eq!(gains[1].proceeds, 200.0);
+        assert_eq!(get_remaining(&lots), [0.0, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn lifo_matches_newest_lots() {
+        let mut lots = get_lots();
+        let (gains, _) = match_disposals(
+            &mut lots,
+            &[get_disposal(1.5, Some(400.0), 10)],
+            LotMethod::Lifo,
+        );
+        assert_eq!(gains[0].acquired, Some(get_date(3)));
+        assert_eq!(gains[1].acquired, Some(get_date(2)));
+        assert_eq!(get_remaining(&lots), [1.0, 0.5, 0.0]);
+    }
+
+    #[test]
+    fn hifo_matches_highest_cost_lots() {
+        let mut lots = get_lots();
+        let (gains, _) = match_disposals(
+            &mut lots,
+            &[get_disposal(1.5, Some(400.0), 10)],
+            LotMethod::Hifo,
+        );
+        assert_eq!(gains[0].cost, 300.0);
+        assert_eq!(gains[1].cost, 100.0);
+        assert_eq!(get_remaining(&lots), [1.0, 0.0, 0.5]);
+    }
+
+    #[test]
+    fn partial_disposals_share_a_lot() {
+        let mut lots = get_lots();
+        let disposals = [
+            get_disposal(0.25, Some(400.0), 10),
+            get_disposal(0.25, Some(500.0), 11),
+        ];
+        let (gains, _) = match_disposals(&mut lots, &disposals, LotMethod::Fifo);
+        assert_eq!(gains.len(), 2);
+        assert!(gains.iter().all(|g| g.acquired == Some(get_date(1))));
+        assert_eq!(gains[1].gain(), 100.0);
+        assert_eq!(get_remaining(&lots), [0.5, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn unmatched_amounts_have_unknown_basis() {
+        let mut lots = get_lots();
+        let (gains, _) = match_disposals(
+            &mut lots,
+            &[get_disposal(4.0, Some(400.0), 10)],
+            LotMethod::Fifo,
+        );
+        let unknown = gains.last().unwrap();
+        assert_eq!(unknown.acquired, None);
+        assert_eq!(unknown.amount, 1.0);
+        assert_eq!(unknown.cost, 0.0);
+        assert!(!unknown.is_long_term());
+        assert_eq!(get_remaining(&lots), [0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn lots_acquired_after_the_sale_are_not_matched() {
+        let mut lots = get_lots();
+        let (gains, _) = match_disposals(
+            &mut lots,
+            &[get_disposal(1.5, Some(400.0), 2)],
+            LotMethod::Lifo,
+        );
+        assert_eq!(gains[0].acquired, Some(get_date(2)));
+        assert_eq!(gains[1].acquired, Some(get_date(1)));
+        assert_eq!(get_remaining(&lots), [0.5, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn unpriced_disposals_are_counted_and_skipped() {
+        let mut lots = get_lots();
+        let (gains, unpriced) =
+            match_disposals(&mut lots, &[get_disposal(1.0, None, 10)], LotMethod::Fifo);
+        assert!(gains.is_empty());
+        assert_eq!(unpriced, 1);
+        assert_eq!(get_remaining(&lots), [1.0, 1.0, 1.0]);
+    }
+}
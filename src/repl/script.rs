@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use super::Repl;
+
+/// State of a running script, changed by its `set -e`, `set +e` and `NAME=value` lines
+#[derive(Default)]
+struct Script {
+    stop_on_error: bool,
+    variables: HashMap<String, String>,
+}
+
+impl Script {
+    fn is_variable_name(name: &str) -> bool {
+        !name.is_empty()
+            && !name.starts_with(|c: char| c.is_ascii_digit())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    }
+    /// Value of a variable set by the script, or else of the environment variable
+    fn get_variable(&self, name: &str) -> Result<String, String> {
+        match self.variables.get(name) {
+            Some(x) => Ok(x.clone()),
+            None => std::env::var(name).map_err(|_| format!("Undefined variable {name:?}")),
+        }
+    }
+    /// Replaces `$NAME` and `${NAME}` with the value of the variable
+    fn substitute(&self, line: &str) -> Result<String, String> {
+        let mut result = String::new();
+        let mut rest = line;
+        while let Some(start) = rest.find('$') {
+            result.push_str(&rest[..start]);
+            rest = &rest[start + 1..];
+            let (name, len) = match rest.strip_prefix('{') {
+                Some(braced) => match braced.find('}') {
+                    Some(end) => (&braced[..end], end + 2),
+                    None => return Err("Unclosed ${ in line".to_string()),
+                },
+                None => {
+                    let end = rest
+                        .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                        .unwrap_or(rest.len());
+                    (&rest[..end], end)
+                }
+            };
+            if !Self::is_variable_name(name) {
+                return Err(format!("Invalid variable name {name:?}"));
+            }
+            result.push_str(&self.get_variable(name)?);
+            rest = &rest[len..];
+        }
+        result.push_str(rest);
+        Ok(result)
+    }
+    /// Handles the lines that configure the script, returning false for commands
+    fn handle_directive(&mut self, line: &str) -> Result<bool, String> {
+        match line {
+            "set -e" => self.stop_on_error = true,
+            "set +e" => self.stop_on_error = false,
+            _ => match line.split_once('=') {
+                Some((name, value)) if Self::is_variable_name(name) => {
+                    let value = self.substitute(value.trim())?;
+                    self.variables.insert(name.to_string(), value);
+                }
+                _ => return Ok(false),
+            },
+        }
+        Ok(true)
+    }
+}
+
+impl Repl {
+    /// Runs the commands of a file line by line, saving the config once at the end
+    pub(super) async fn run_script(&mut self, path: &str) -> Result<(), String> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(x) => x,
+            _ => return Err(format!("Could not read {path}")),
+        };
+        // Nested scripts leave saving to the outermost one
+        let is_outermost = !self.is_running_script;
+        self.is_running_script = true;
+        let result = self.run_script_lines(path, &contents).await;
+        if is_outermost {
+            self.is_running_script = false;
+            self.is_script_exited = false;
+            if self.has_unsaved_config {
+                self.store_config_to_data_file()?;
+            }
+        }
+        result
+    }
+    async fn run_script_lines(&mut self, path: &str, contents: &str) -> Result<(), String> {
+        let mut script = Script::default();
+        for (i, line) in contents.lines().enumerate() {
+            // Also stops the scripts sourcing this one
+            if self.is_script_exited {
+                break;
+            }
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let result = match script.handle_directive(line) {
+                Ok(true) => Ok(()),
+                Ok(false) => match script.substitute(line) {
+                    Ok(command) => self.handle_command(&command).await,
+                    Err(err) => Err(err),
+                },
+                Err(err) => Err(err),
+            };
+            if let Err(err) = result {
+                let err = format!("{path}:{}: {err}", i + 1);
+                if script.stop_on_error {
+                    return Err(err);
+                }
+                eprintln!("{err}");
+            }
+        }
+        Ok(())
+    }
+    pub(super) async fn handle_source(&mut self, command_parts: &[&str]) -> Result<(), String> {
+        match command_parts.len() {
+            1 => Box::pin(self.run_script(command_parts[0])).await,
+            _ => Repl::get_bad_argument_count_err(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Script;
+
+    fn get_script() -> Script {
+        let mut script = Script::default();
+        script
+            .variables
+            .insert("ACCOUNT".to_string(), "0xabc".to_string());
+        script
+    }
+
+    #[test]
+    fn substitute_replaces_variables() {
+        let script = get_script();
+        assert_eq!(
+            script.substitute("account add evm $ACCOUNT main"),
+            Ok("account add evm 0xabc main".to_string())
+        );
+        assert_eq!(
+            script.substitute("echo ${ACCOUNT}_1 $ACCOUNT"),
+            Ok("echo 0xabc_1 0xabc".to_string())
+        );
+        assert_eq!(script.substitute("balance"), Ok("balance".to_string()));
+    }
+
+    #[test]
+    fn substitute_rejects_bad_variables() {
+        let script = get_script();
+        assert!(script.substitute("echo ${ACCOUNT").is_err());
+        assert!(script.substitute("echo $1").is_err());
+        assert!(script.substitute("echo $").is_err());
+        assert!(script
+            .substitute("echo $BOP_TEST_UNDEFINED_VARIABLE")
+            .is_err());
+    }
+
+    #[test]
+    fn directives_set_state() {
+        let mut script = get_script();
+        assert_eq!(script.handle_directive("set -e"), Ok(true));
+        assert!(script.stop_on_error);
+        assert_eq!(script.handle_directive("NAME=$ACCOUNT"), Ok(true));
+        assert_eq!(script.variables.get("NAME"), Some(&"0xabc".to_string()));
+        assert_eq!(script.handle_directive("balance"), Ok(false));
+    }
+}
@@ -0,0 +1,254 @@
+use chrono::{DateTime, Local, TimeZone};
+use futures::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    chain::{token::Token, ChainOps},
+    defillama,
+    utils::{support_option::SupportOption, table::Table},
+};
+
+use super::{
+    cost::{format_price, CostLot},
+    data_file::TRANSACTIONS_FILE,
+    Repl,
+};
+
+/// Token transfer imported from the history of an account
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Transaction {
+    /// Chain-id
+    pub chain: String,
+    pub account: String,
+    pub hash: String,
+    /// Unix timestamp in seconds
+    pub timestamp: i64,
+    pub token: Token,
+    pub amount: f64,
+    pub is_incoming: bool,
+    /// Whether the other side of the transfer is another tracked account
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_internal: bool,
+    /// USD price of the token at the time of the transfer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price: Option<f64>,
+}
+
+impl Transaction {
+    fn is_same_transfer(&self, other: &Transaction) -> bool {
+        self.chain == other.chain
+            && self.account == other.account
+            && self.hash == other.hash
+            && self.token.address == other.token.address
+            && self.is_incoming == other.is_incoming
+    }
+    pub fn format_date(&self) -> String {
+        match Local.timestamp_opt(self.timestamp, 0).single() {
+            Some(x) => x.format("%Y-%m-%d %H:%M").to_string(),
+            None => self.timestamp.to_string(),
+        }
+    }
+    /// Transfers between tracked accounts move tokens around without acquiring them
+    pub fn is_acquisition(&self) -> bool {
+        self.is_incoming && !self.is_internal
+    }
+    pub fn is_disposal(&self) -> bool {
+        !self.is_incoming && !self.is_internal
+    }
+    pub fn to_lot(&self) -> Option<CostLot> {
+        if !self.is_acquisition() {
+            return None;
+        }
+        Some(CostLot {
+            token: self.token.clone(),
+            amount: self.amount,
+            price: self.price?,
+            date: DateTime::from_timestamp(self.timestamp, 0)?.date_naive(),
+        })
+    }
+}
+
+impl Repl {
+    pub(super) fn read_transactions(&self) -> Result<Vec<Transaction>, String> {
+        self.read_encrypted_file(TRANSACTIONS_FILE)
+    }
+    pub(super) fn store_transactions(&self, transactions: &[Transaction]) -> Result<(), String> {
+        self.store_encrypted_file(TRANSACTIONS_FILE, &transactions)
+    }
+    async fn import_transactions(&self, chain_id: &str, account: &str) -> Result<(), String> {
+        let chain = self.find_chain(chain_id)?;
+        let (chain_type, address) = self.find_account_address(account)?;
+        if *chain_type != chain.chain_type {
+            return Err(format!(
+                "{account} is not an account of chain-type {}",
+                chain.chain_type
+            ));
+        }
+        let tokens = self
+            .tokens_of_chain(chain)
+            .map(|(_, t)| t.clone())
+            .collect::<Vec<_>>();
+        self.spinner.start(Some("Fetching transfers..."));
+        // Histories are heavy to fetch, so each RPC is tried once instead of retrying
+        let mut transfers = None;
+        for rpc_index in 0..chain.properties.rpc_urls.len() {
+            match chain.get_transfers(address, &tokens, rpc_index).await {
+                SupportOption::SupportedSome(x) => {
+                    transfers = Some(x);
+                    break;
+                }
+                SupportOption::SupportedNone => continue,
+                SupportOption::Unsupported => break,
+            }
+        }
+        self.spinner.stop();
+        let history = match transfers {
+            Some(x) => x,
+            None => {
+                return Err(format!(
+                    "Could not fetch transfers, the {} RPCs may not serve the whole history, \
+                    try setting one that does with \"chain set\"",
+                    chain.properties.name
+                ))
+            }
+        };
+        let tracked_addresses = self
+            .accounts_of_type(&chain.chain_type)
+            .filter_map(|(_, a, _)| chain.parse_wallet_address(a))
+            .collect::<Vec<_>>();
+        let mut transactions = self.read_transactions()?;
+        let new_transactions = history
+            .transfers
+            .into_iter()
+            .map(|t| Transaction {
+                chain: chain_id.to_string(),
+                account: address.clone(),
+                hash: t.hash,
+                timestamp: t.timestamp,
+                amount: t.token.format(&t.amount),
+                token: t.token,
+                is_incoming: t.is_incoming,
+                is_internal: t
+                    .counterparty
+                    .and_then(|c| chain.parse_wallet_address(&c))
+                    .is_some_and(|c| tracked_addresses.contains(&c)),
+                price: None,
+            })
+            .filter(|t| !transactions.iter().any(|x| x.is_same_transfer(t)))
+            .collect::<Vec<_>>();
+        self.spinner.set_total(new_transactions.len());
+        self.spinner.start(Some("Fetching historical prices..."));
+        let prices = stream::iter(new_transactions.iter())
+            .map(async |t| {
+                let price = match defillama::prices::get_coin_id(chain_id, &t.token.address) {
+                    Some(coin_id) => {
+                        defillama::prices::get_historical_price(&coin_id, t.timestamp).await
+                    }
+                    None => None,
+                };
+                self.spinner.inc_progress();
+                price
+            })
+            .buffered(5)
+            .collect::<Vec<_>>()
+            .await;
+        self.spinner.stop();
+        let new_len = new_transactions.len();
+        let unpriced_len = prices.iter().filter(|p| p.is_none()).count();
+        transactions.extend(
+            new_transactions
+                .into_iter()
+                .zip(prices)
+                .map(|(t, price)| Transaction { price, ..t }),
+        );
+        transactions.sort_by_key(|t| t.timestamp);
+        self.store_transactions(&transactions)?;
+        println!("{new_len} new transactions imported");
+        if history.skipped_count > 0 {
+            println!(
+                "Could not read {} transactions, they were left out",
+                history.skipped_count
+            );
+        }
+        if history.is_truncated {
+            println!("The history is too long to fetch, older transfers were left out");
+        }
+        if unpriced_len > 0 {
+            println!("Could not find the historical price of {unpriced_len} of them");
+        }
+        Ok(())
+    }
+    pub(super) async fn handle_transactions(
+        &mut self,
+        command_parts: &[&str],
+    ) -> Result<(), String> {
+        match command_parts.len() {
+            0 => {
+                let transactions = self.read_transactions()?;
+                if transactions.is_empty() {
+                    println!("You have no imported transactions");
+                    return Ok(());
+                }
+                let mut rows = transactions
+                    .iter()
+                    .map(|t| {
+                        let alias = self
+                            .config
+                            .accounts
+                            .iter()
+                            .find(|(_, address, _)| *address == t.account)
+                            .and_then(|(_, _, alias)| alias.clone());
+                        Vec::from([
+                            t.format_date(),
+                            t.chain.clone(),
+                            Repl::format_account(&t.account, &alias),
+                            t.token.symbol.clone(),
+                            format!("{}{}", if t.is_incoming { "+" } else { "-" }, t.amount),
+                            t.price.map(format_price).unwrap_or("-".to_string()),
+                            if t.is_internal {
+                                "Internal".to_string()
+                            } else {
+                                String::new()
+                            },
+                        ])
+                    })
+                    .collect::<Vec<_>>();
+                rows.insert(
+                    0,
+                    Vec::from([
+                        "Date".to_string(),
+                        "Chain".to_string(),
+                        "Account".to_string(),
+                        "Token".to_string(),
+                        "Amount".to_string(),
+                        "Price (USD)".to_string(),
+                        String::new(),
+                    ]),
+                );
+                let mut t = Table::from(rows);
+                t.title = "Transactions".to_string();
+                println!("{t}");
+                Ok(())
+            }
+            3 => match command_parts[0] {
+                "import" => {
+                    self.import_transactions(command_parts[1], command_parts[2])
+                        .await
+                }
+                "rm" => {
+                    let chain_id = command_parts[1];
+                    self.find_chain(chain_id)?;
+                    let (_, address) = self.find_account_address(command_parts[2])?;
+                    let mut transactions = self.read_transactions()?;
+                    let len = transactions.len();
+                    transactions.retain(|t| t.chain != chain_id || t.account != *address);
+                    self.store_transactions(&transactions)?;
+                    println!("{} transactions removed", len - transactions.len());
+                    Ok(())
+                }
+                _ => Repl::get_unknown_option_expecting_or_err(&["import", "rm"]),
+            },
+            _ => Repl::get_bad_argument_count_err(),
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+use ratatui::{
+    crossterm::event::{KeyCode, KeyEvent},
+    widgets::TableState,
+};
+
+use crate::repl::balance::{ReplBalanceEntry, ReplBalances};
+
+/// Result of querying the first RPC of a chain
+pub enum RpcStatus {
+    Ok(Duration),
+    Failing,
+    TimedOut,
+}
+
+/// Everything displayed by the dashboard, fetched at once
+pub struct Dashboard {
+    pub balances: ReplBalances,
+    /// Vec of chain name and USD value
+    pub chain_allocation: Vec<(String, f64)>,
+    /// Vec of chain name, RPC url and status
+    pub rpc_statuses: Vec<(String, String, RpcStatus)>,
+    /// Vec of snapshot timestamp and total USD value
+    pub history: Vec<(f64, f64)>,
+    pub fetched_at: DateTime<Local>,
+    /// Error that left part of the dashboard empty, e.g. an unreadable history
+    pub warning: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SortColumn {
+    Value,
+    Token,
+    Chain,
+    Account,
+}
+
+impl SortColumn {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Value => "value",
+            Self::Token => "token",
+            Self::Chain => "chain",
+            Self::Account => "account",
+        }
+    }
+    fn next(&self) -> Self {
+        match self {
+            Self::Value => Self::Token,
+            Self::Token => Self::Chain,
+            Self::Chain => Self::Account,
+            Self::Account => Self::Value,
+        }
+    }
+}
+
+pub enum Action {
+    Quit,
+    Refresh,
+}
+
+pub struct App {
+    pub dashboard: Dashboard,
+    pub sort_column: SortColumn,
+    pub is_ascending: bool,
+    pub filter: String,
+    /// Whether keys are typed into the filter
+    pub is_filtering: bool,
+    pub holdings_state: TableState,
+    /// Message displayed in the footer, e.g. the last refresh error
+    pub status: Option<String>,
+}
+
+impl App {
+    pub fn new(dashboard: Dashboard) -> Self {
+        Self {
+            sort_column: SortColumn::Value,
+            is_ascending: false,
+            filter: String::new(),
+            is_filtering: false,
+            holdings_state: TableState::default().with_selected(0),
+            status: dashboard.warning.clone(),
+            dashboard,
+        }
+    }
+    /// Holdings matching the filter, in the selected order
+    pub fn get_holdings(&self) -> Vec<&ReplBalanceEntry> {
+        let filter = self.filter.to_lowercase();
+        let mut holdings = self
+            .dashboard
+            .balances
+            .entries
+            .iter()
+            .filter(|entry| {
+                [&entry.account, &entry.chain, &entry.label()]
+                    .iter()
+                    .any(|field| field.to_lowercase().contains(&filter))
+            })
+            .collect::<Vec<_>>();
+        holdings.sort_by(|a, b| match self.sort_column {
+            SortColumn::Value => a.balance_usd.total_cmp(&b.balance_usd),
+            SortColumn::Token => a.token.symbol.cmp(&b.token.symbol),
+            SortColumn::Chain => a.chain.cmp(&b.chain),
+            SortColumn::Account => a.account.cmp(&b.account),
+        });
+        if !self.is_ascending {
+            holdings.reverse();
+        }
+        holdings
+    }
+    fn select(&mut self, offset: isize) {
+        let len = self.get_holdings().len();
+        let selected = self.holdings_state.selected().unwrap_or(0);
+        let selected = selected.saturating_add_signed(offset);
+        self.holdings_state
+            .select(Some(selected.min(len.saturating_sub(1))));
+    }
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<Action> {
+        if self.is_filtering {
+            match key.code {
+                KeyCode::Char(c) => self.filter.push(c),
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                }
+                KeyCode::Enter => self.is_filtering = false,
+                KeyCode::Esc => {
+                    self.filter.clear();
+                    self.is_filtering = false;
+                }
+                _ => {}
+            }
+            self.holdings_state.select(Some(0));
+            return None;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Some(Action::Quit),
+            KeyCode::Char('r') => return Some(Action::Refresh),
+            KeyCode::Char('/') => self.is_filtering = true,
+            KeyCode::Char('s') => self.sort_column = self.sort_column.next(),
+            KeyCode::Char('o') => self.is_ascending = !self.is_ascending,
+            KeyCode::Down | KeyCode::Char('j') => self.select(1),
+            KeyCode::Up | KeyCode::Char('k') => self.select(-1),
+            KeyCode::PageDown => self.select(10),
+            KeyCode::PageUp => self.select(-10),
+            _ => {}
+        }
+        None
+    }
+}
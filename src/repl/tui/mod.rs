@@ -0,0 +1,127 @@
+mod app;
+mod ui;
+
+use std::time::{Duration, Instant};
+
+use app::{Action, App, Dashboard, RpcStatus};
+use chrono::Local;
+use futures::{stream, StreamExt};
+use ratatui::{
+    crossterm::event::{self, Event, KeyEventKind},
+    DefaultTerminal,
+};
+
+use crate::chain::ChainOps;
+
+use super::Repl;
+
+/// Time given to a chain RPC to answer before it is considered down
+static RPC_TIMEOUT: Duration = Duration::from_secs(5);
+
+impl Repl {
+    /// Queries the native balance of an account on the first RPC of each enabled chain that
+    /// has accounts
+    async fn get_rpc_statuses(&self) -> Vec<(String, String, RpcStatus)> {
+        let chains = self
+            .enabled_chains()
+            .filter_map(|chain| {
+                let (_, address, _) = self.accounts_of_type(&chain.chain_type).next()?;
+                Some((chain, address))
+            })
+            .collect::<Vec<_>>();
+        stream::iter(chains)
+            .map(async |(chain, address)| {
+                let start = Instant::now();
+                let status = match tokio::time::timeout(
+                    RPC_TIMEOUT,
+                    chain.get_native_token_balance(address, 0),
+                )
+                .await
+                {
+                    Ok((Some(_), _)) => RpcStatus::Ok(start.elapsed()),
+                    Ok((None, _)) => RpcStatus::Failing,
+                    Err(_) => RpcStatus::TimedOut,
+                };
+                let url = chain
+                    .properties
+                    .rpc_urls
+                    .first()
+                    .and_then(|url| url.host_str())
+                    .unwrap_or_default()
+                    .to_string();
+                (chain.properties.name.clone(), url, status)
+            })
+            .buffered(20)
+            .collect()
+            .await
+    }
+    async fn fetch_dashboard(&self) -> Result<Dashboard, String> {
+        let balances = self.fetch_balances().await?;
+        let chain_allocation = self.group_balances(&balances, "chain");
+        // The rest of the dashboard is still worth showing without the history chart
+        let (history, warning) = match self.read_history() {
+            Ok(history) => (
+                history
+                    .iter()
+                    .map(|snapshot| (snapshot.timestamp as f64, snapshot.total_usd))
+                    .collect(),
+                None,
+            ),
+            Err(err) => (Vec::new(), Some(err)),
+        };
+        Ok(Dashboard {
+            balances,
+            chain_allocation,
+            rpc_statuses: self.get_rpc_statuses().await,
+            history,
+            fetched_at: Local::now(),
+            warning,
+        })
+    }
+    async fn run_dashboard(&mut self, terminal: &mut DefaultTerminal, app: &mut App) {
+        loop {
+            if terminal.draw(|frame| ui::draw(frame, app)).is_err() {
+                return;
+            }
+            let key = match event::read() {
+                Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => key,
+                _ => continue,
+            };
+            match app.handle_key(key) {
+                Some(Action::Quit) => return,
+                Some(Action::Refresh) => {
+                    app.status = Some("Refreshing...".to_string());
+                    let _ = terminal.draw(|frame| ui::draw(frame, app));
+                    app.status = match self.fetch_dashboard().await {
+                        Ok(dashboard) => {
+                            app.dashboard = dashboard;
+                            app.dashboard.warning.clone()
+                        }
+                        Err(err) => Some(err),
+                    };
+                    // Warnings printed while fetching are drawn over
+                    let _ = terminal.clear();
+                }
+                None => {}
+            }
+        }
+    }
+    pub(super) async fn handle_tui(&mut self, command_parts: &[&str]) -> Result<(), String> {
+        if !command_parts.is_empty() {
+            return Repl::get_bad_argument_count_err();
+        }
+        let dashboard = self.fetch_dashboard().await?;
+        let mut terminal = match ratatui::try_init() {
+            Ok(x) => x,
+            _ => return Err("Could not open the dashboard in this terminal".to_string()),
+        };
+        // The spinner would be drawn over the dashboard when refreshing
+        let is_spinner_hidden = self.spinner.is_hidden;
+        self.spinner.is_hidden = true;
+        self.run_dashboard(&mut terminal, &mut App::new(dashboard))
+            .await;
+        self.spinner.is_hidden = is_spinner_hidden;
+        ratatui::restore();
+        Ok(())
+    }
+}
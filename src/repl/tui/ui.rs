@@ -0,0 +1,237 @@
+use chrono::{Local, TimeZone};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    symbols::Marker,
+    text::{Line, Span},
+    widgets::{
+        Axis, Bar, BarChart, BarGroup, Block, Cell, Chart, Dataset, GraphType, Paragraph, Row,
+        Table,
+    },
+    Frame,
+};
+
+use crate::utils::float::ExtendFloat;
+
+use super::app::{App, RpcStatus};
+
+fn format_timestamp(timestamp: f64) -> String {
+    match Local.timestamp_opt(timestamp as i64, 0).single() {
+        Some(x) => x.format("%Y-%m-%d").to_string(),
+        None => timestamp.to_string(),
+    }
+}
+
+fn draw_total(frame: &mut Frame, area: Rect, app: &App) {
+    let dashboard = &app.dashboard;
+    let total = dashboard.balances.total_usd();
+    let mut lines = Vec::from([
+        Line::from(Span::styled(
+            format!("{} USD", total.round_to_fixed_string(2)),
+            Style::new().fg(Color::Green).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(format!("Holdings: {}", dashboard.balances.entries.len())),
+    ]);
+    if let Some((_, last_total)) = dashboard.history.last() {
+        lines.push(Line::from(format!(
+            "Since last snapshot: {} USD",
+            (total - last_total).round_to_signed_string(2)
+        )));
+    }
+    lines.push(Line::from(format!(
+        "Refreshed at {}",
+        dashboard.fetched_at.format("%H:%M:%S")
+    )));
+    let paragraph = Paragraph::new(lines).block(Block::bordered().title("Total value"));
+    frame.render_widget(paragraph, area);
+}
+
+fn draw_chain_allocation(frame: &mut Frame, area: Rect, app: &App) {
+    let total = app.dashboard.balances.total_usd();
+    let bars = app
+        .dashboard
+        .chain_allocation
+        .iter()
+        .map(|(chain, value)| {
+            let share = if total == 0.0 {
+                0.0
+            } else {
+                value / total * 100.0
+            };
+            Bar::default()
+                .label(Line::from(chain.clone()))
+                .value(share.max(0.0).round() as u64)
+                .text_value(format!("{}%", share.round_to_fixed_string(2)))
+        })
+        .collect::<Vec<_>>();
+    let chart = BarChart::default()
+        .block(Block::bordered().title("Allocation by chain"))
+        .direction(Direction::Horizontal)
+        .bar_width(1)
+        .bar_gap(0)
+        .max(100)
+        .bar_style(Style::new().fg(Color::Green))
+        .data(BarGroup::default().bars(&bars));
+    frame.render_widget(chart, area);
+}
+
+fn draw_rpc_statuses(frame: &mut Frame, area: Rect, app: &App) {
+    let rows = app
+        .dashboard
+        .rpc_statuses
+        .iter()
+        .map(|(chain, url, status)| {
+            let status = match status {
+                RpcStatus::Ok(latency) => Cell::from(format!("{} ms", latency.as_millis()))
+                    .style(Style::new().fg(Color::Green)),
+                RpcStatus::Failing => Cell::from("Failing").style(Style::new().fg(Color::Red)),
+                RpcStatus::TimedOut => {
+                    Cell::from("Timed out").style(Style::new().fg(Color::Yellow))
+                }
+            };
+            Row::new([Cell::from(chain.clone()), status, Cell::from(url.clone())])
+        });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(14),
+            Constraint::Length(10),
+            Constraint::Fill(1),
+        ],
+    )
+    .block(Block::bordered().title("RPC status"));
+    frame.render_widget(table, area);
+}
+
+fn draw_holdings(frame: &mut Frame, area: Rect, app: &mut App) {
+    let total = app.dashboard.balances.total_usd();
+    let holdings = app.get_holdings();
+    let rows = holdings
+        .iter()
+        .map(|entry| {
+            let share = if total == 0.0 {
+                0.0
+            } else {
+                entry.balance_usd / total * 100.0
+            };
+            Row::new([
+                entry.account.clone(),
+                entry.chain.clone(),
+                entry.label(),
+                entry.token.format(&entry.balance_native).to_string(),
+                entry.balance_usd.round_to_fixed_string(2),
+                format!("{}%", share.round_to_fixed_string(2)),
+            ])
+        })
+        .collect::<Vec<_>>();
+    let title = format!(
+        "Holdings ({}/{}) by {} {}{}",
+        rows.len(),
+        app.dashboard.balances.entries.len(),
+        app.sort_column.label(),
+        if app.is_ascending { "↑" } else { "↓" },
+        if app.is_filtering || !app.filter.is_empty() {
+            format!(", filter: {}", app.filter)
+        } else {
+            String::new()
+        }
+    );
+    let header = Row::new([
+        "Account",
+        "Chain",
+        "Token",
+        "Balance",
+        "Balance (USD)",
+        "Share",
+    ])
+    .style(Style::new().fg(Color::Green));
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Length(8),
+        ],
+    )
+    .header(header)
+    .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+    .block(Block::bordered().title(title));
+    frame.render_stateful_widget(table, area, &mut app.holdings_state);
+}
+
+fn draw_history(frame: &mut Frame, area: Rect, app: &App) {
+    let block = Block::bordered().title("History");
+    let history = &app.dashboard.history;
+    if history.len() < 2 {
+        let paragraph =
+            Paragraph::new("Not enough snapshots, record them with \"balance\"").block(block);
+        frame.render_widget(paragraph, area);
+        return;
+    }
+    let (min_x, max_x) = (history[0].0, history[history.len() - 1].0);
+    let (min_y, max_y) = history
+        .iter()
+        .fold((f64::MAX, f64::MIN), |(min, max), (_, y)| {
+            (min.min(*y), max.max(*y))
+        });
+    // Keeps a flat history in the middle of the chart
+    let (min_y, max_y) = if min_y == max_y {
+        (min_y - 1.0, max_y + 1.0)
+    } else {
+        (min_y, max_y)
+    };
+    let dataset = Dataset::default()
+        .marker(Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::new().fg(Color::Green))
+        .data(history);
+    let chart = Chart::new(Vec::from([dataset]))
+        .block(block)
+        .x_axis(
+            Axis::default()
+                .bounds([min_x, max_x])
+                .labels([format_timestamp(min_x), format_timestamp(max_x)]),
+        )
+        .y_axis(Axis::default().bounds([min_y, max_y]).labels([
+            min_y.round_to_fixed_string(2),
+            max_y.round_to_fixed_string(2),
+        ]));
+    frame.render_widget(chart, area);
+}
+
+fn draw_footer(frame: &mut Frame, area: Rect, app: &App) {
+    let text = match (&app.status, app.is_filtering) {
+        (_, true) => "Type to filter, enter to apply, esc to clear".to_string(),
+        (Some(status), false) => status.clone(),
+        (None, false) => "q quit  r refresh  / filter  s sort  o order  ↑↓ select".to_string(),
+    };
+    frame.render_widget(
+        Paragraph::new(text).style(Style::new().add_modifier(Modifier::DIM)),
+        area,
+    );
+}
+
+pub fn draw(frame: &mut Frame, app: &mut App) {
+    let [main, footer] =
+        Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+    let [left, right] =
+        Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)]).areas(main);
+    let rpc_height = app.dashboard.rpc_statuses.len() as u16 + 2;
+    let [total, allocation, rpcs] = Layout::vertical([
+        Constraint::Length(6),
+        Constraint::Fill(1),
+        Constraint::Max(rpc_height),
+    ])
+    .areas(left);
+    let [holdings, history] =
+        Layout::vertical([Constraint::Fill(1), Constraint::Length(12)]).areas(right);
+    draw_total(frame, total, app);
+    draw_chain_allocation(frame, allocation, app);
+    draw_rpc_statuses(frame, rpcs, app);
+    draw_holdings(frame, holdings, app);
+    draw_history(frame, history, app);
+    draw_footer(frame, footer, app);
+}
@@ -0,0 +1,186 @@
+use std::{
+    collections::HashMap,
+    io,
+    time::{Duration, Instant},
+};
+
+use chrono::Local;
+use crossterm::{
+    cursor::MoveTo,
+    event::{self, Event, KeyEventKind},
+    execute,
+    terminal::{self, Clear, ClearType},
+};
+
+use crate::utils::{float::ExtendFloat, table::Table};
+
+use super::{
+    balance::{ReplBalanceEntry, ReplBalances},
+    cost::format_price,
+    Repl,
+};
+
+/// Seconds between balance refreshes when none are given
+static DEFAULT_WATCH_INTERVAL: u64 = 60;
+/// Shortest interval allowed, to stay clear of RPC rate limits
+static MIN_WATCH_INTERVAL: u64 = 5;
+
+/// Identifies a holding across refreshes, as chain, account and token address
+fn get_entry_key(entry: &ReplBalanceEntry) -> (String, String, String) {
+    (
+        entry.chain.clone(),
+        entry.account.clone(),
+        entry.token.address.clone(),
+    )
+}
+
+/// USD value of a single unit of the holding
+fn get_unit_price(entry: &ReplBalanceEntry) -> Option<f64> {
+    let amount = entry.token.format(&entry.balance_native);
+    (amount != 0.0).then(|| (entry.balance_usd / amount).abs())
+}
+
+/// Waits until a key is pressed or the timeout is over, returning whether a key was pressed
+fn wait_for_keypress(timeout: Duration) -> Result<bool, String> {
+    if terminal::enable_raw_mode().is_err() {
+        return Err("Could not read keys from the terminal".to_string());
+    }
+    let deadline = Instant::now() + timeout;
+    let mut is_pressed = false;
+    while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+        if !event::poll(remaining).unwrap_or(false) {
+            break;
+        }
+        if let Ok(Event::Key(key)) = event::read() {
+            if key.kind == KeyEventKind::Press {
+                is_pressed = true;
+                break;
+            }
+        }
+    }
+    let _ = terminal::disable_raw_mode();
+    Ok(is_pressed)
+}
+
+impl Repl {
+    /// Table of the balances, highlighting the holdings that changed since the previous
+    /// refresh
+    fn get_watch_table(&self, balances: &ReplBalances, previous: Option<&ReplBalances>) -> Table {
+        let previous_entries = previous
+            .map(|previous| {
+                previous
+                    .entries
+                    .iter()
+                    .map(|entry| (get_entry_key(entry), entry))
+                    .collect::<HashMap<_, _>>()
+            })
+            .unwrap_or_default();
+        let mut highlighted_rows = Vec::new();
+        let mut rows = balances
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                let previous_entry = previous_entries.get(&get_entry_key(entry));
+                let is_changed = match previous_entry {
+                    Some(previous_entry) => {
+                        previous_entry.balance_native != entry.balance_native
+                            || (previous_entry.balance_usd - entry.balance_usd).abs() >= 0.01
+                    }
+                    None => previous.is_some(),
+                };
+                if is_changed {
+                    // Shifted by the header row
+                    highlighted_rows.push(i + 1);
+                }
+                let price = get_unit_price(entry);
+                let price_move = match (previous_entry.and_then(|e| get_unit_price(e)), price) {
+                    (Some(previous_price), Some(price)) if previous_price != 0.0 => format!(
+                        "{}%",
+                        ((price - previous_price) / previous_price * 100.0)
+                            .round_to_signed_string(2)
+                    ),
+                    _ => "-".to_string(),
+                };
+                Vec::from([
+                    entry.account.clone(),
+                    entry.chain.clone(),
+                    entry.label(),
+                    entry.token.format(&entry.balance_native).to_string(),
+                    entry.balance_usd.round_to_fixed_string(2),
+                    price.map(format_price).unwrap_or("-".to_string()),
+                    price_move,
+                ])
+            })
+            .collect::<Vec<_>>();
+        rows.insert(
+            0,
+            Vec::from([
+                "Account".to_string(),
+                "Chain".to_string(),
+                "Token".to_string(),
+                "Balance".to_string(),
+                "Balance (USD)".to_string(),
+                "Price (USD)".to_string(),
+                "Price move".to_string(),
+            ]),
+        );
+        let mut t = Table::from(rows);
+        t.title = "Balances".to_string();
+        t.highlighted_rows = highlighted_rows;
+        t
+    }
+    fn display_watch(
+        &self,
+        balances: &ReplBalances,
+        previous: Option<&ReplBalances>,
+        interval: u64,
+    ) {
+        // Drawn over the previous refresh
+        let _ = execute!(io::stdout(), MoveTo(0, 0), Clear(ClearType::All));
+        println!("{}", self.get_watch_table(balances, previous));
+        let total = balances.total_usd();
+        println!(
+            "Balance: {} USD{}",
+            total.round_to_fixed_string(2),
+            previous
+                .map(|previous| format!(
+                    " ({} since last refresh)",
+                    (total - previous.total_usd()).round_to_signed_string(2)
+                ))
+                .unwrap_or_default()
+        );
+        println!(
+            "Refreshed at {}, every {interval}s. Press any key to stop.",
+            Local::now().format("%H:%M:%S")
+        );
+    }
+    pub(super) async fn watch_balances(&self, interval: Option<&str>) -> Result<(), String> {
+        if self.is_json_output() {
+            return Err("Watching is only available with table output".to_string());
+        }
+        let interval = match interval.map(|x| x.parse::<u64>()) {
+            None => DEFAULT_WATCH_INTERVAL,
+            Some(Ok(x)) if x >= MIN_WATCH_INTERVAL => x,
+            _ => {
+                return Err(format!(
+                    "Invalid interval, expecting at least {MIN_WATCH_INTERVAL} seconds"
+                ))
+            }
+        };
+        let mut previous = None;
+        loop {
+            match self.fetch_balances().await {
+                Ok(balances) => {
+                    self.display_watch(&balances, previous.as_ref(), interval);
+                    previous = Some(balances);
+                }
+                // Kept watching, as RPCs may fail only for a moment
+                Err(err) => eprintln!("{err}"),
+            }
+            if wait_for_keypress(Duration::from_secs(interval))? {
+                return Ok(());
+            }
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use std::fmt::Display;
+
+use super::{float::ExtendFloat, text::StylizedText};
+
+static SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+pub fn terminal_width() -> usize {
+    match crossterm::terminal::size() {
+        Ok((columns, _)) => columns as usize,
+        _ => 80,
+    }
+}
+
+/// Picks `width` values out of `values`, repeating or skipping them as needed
+fn resample(values: &[f64], width: usize) -> Vec<f64> {
+    (0..width)
+        .map(|i| values[i * values.len() / width])
+        .collect()
+}
+
+pub fn sparkline(values: &[f64], width: usize) -> String {
+    if values.is_empty() || width == 0 {
+        return String::new();
+    }
+    let values = resample(values, width.min(values.len()));
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    values
+        .iter()
+        .map(|v| {
+            if max == min {
+                return SPARKS[SPARKS.len() / 2];
+            }
+            SPARKS[((v - min) / (max - min) * (SPARKS.len() - 1) as f64).round() as usize]
+        })
+        .collect()
+}
+
+static BAR_EIGHTHS: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+/// Horizontal bar filling `fraction` of `width` columns
+pub fn bar(fraction: f64, width: usize) -> String {
+    let eighths = (fraction.clamp(0.0, 1.0) * (width * 8) as f64).round() as usize;
+    let mut bar = "█".repeat(eighths / 8);
+    let partial = eighths % 8;
+    if partial > 0 {
+        bar.push(BAR_EIGHTHS[partial]);
+    }
+    bar
+}
+
+/// Line chart drawn with box-drawing characters
+#[derive(Debug)]
+pub struct Chart {
+    values: Vec<f64>,
+    pub title: String,
+    pub height: usize,
+    pub width: usize,
+    /// Labels displayed under the start and the end of the x axis
+    pub x_labels: (String, String),
+}
+
+impl From<Vec<f64>> for Chart {
+    fn from(value: Vec<f64>) -> Self {
+        Self {
+            values: value,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for Chart {
+    fn default() -> Self {
+        Self {
+            values: Vec::default(),
+            title: String::default(),
+            height: 10,
+            width: terminal_width(),
+            x_labels: (String::default(), String::default()),
+        }
+    }
+}
+
+impl Display for Chart {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.values.is_empty() || self.height < 2 {
+            return write!(f, "");
+        }
+        if self.title != String::default() {
+            let title = self.title.as_str().to_title();
+            writeln!(f, "{title}")?;
+        }
+        let min = self.values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = self
+            .values
+            .iter()
+            .copied()
+            .fold(f64::NEG_INFINITY, f64::max);
+        let y_labels = (0..self.height)
+            .map(|row| min + (max - min) * row as f64 / (self.height - 1) as f64)
+            .map(|value| value.round_to_fixed_string(2))
+            .collect::<Vec<_>>();
+        let label_width = y_labels.iter().map(|l| l.len()).max().unwrap_or_default();
+        let plot_width = self.width.saturating_sub(label_width + 3).max(1);
+        let values = resample(&self.values, plot_width);
+        // Row 0 is the bottom of the chart
+        let to_row = |value: f64| {
+            if max == min {
+                return (self.height - 1) / 2;
+            }
+            ((value - min) / (max - min) * (self.height - 1) as f64).round() as usize
+        };
+        let mut grid = vec![vec![' '; plot_width]; self.height];
+        grid[to_row(values[0])][0] = '─';
+        for x in 1..plot_width {
+            let (y0, y1) = (to_row(values[x - 1]), to_row(values[x]));
+            if y0 == y1 {
+                grid[y0][x] = '─';
+                continue;
+            }
+            let (from, to) = if y1 > y0 {
+                ('╯', '╭')
+            } else {
+                ('╮', '╰')
+            };
+            grid[y0][x] = from;
+            grid[y1][x] = to;
+            for row in grid.iter_mut().take(y0.max(y1)).skip(y0.min(y1) + 1) {
+                row[x] = '│';
+            }
+        }
+        for (row, label) in grid.iter().zip(&y_labels).rev() {
+            let line = row.iter().collect::<String>();
+            writeln!(
+                f,
+                "{label:>label_width$} ┤ {}",
+                line.trim_end().to_colored()
+            )?;
+        }
+        let (start, end) = &self.x_labels;
+        let gap = plot_width.saturating_sub(start.len() + end.len()).max(1);
+        writeln!(
+            f,
+            "{}{start}{}{end}",
+            " ".repeat(label_width + 3),
+            " ".repeat(gap)
+        )
+    }
+}
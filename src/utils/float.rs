@@ -3,6 +3,7 @@
 pub trait ExtendFloat: num_traits::Float {
     fn round_to_fixed(&self, decimals: u8) -> Self;
     fn round_to_fixed_string(&self, decimals: u8) -> String;
+    fn round_to_signed_string(&self, decimals: u8) -> String;
 }
 
 impl ExtendFloat for f64 {
@@ -13,4 +14,7 @@ impl ExtendFloat for f64 {
     fn round_to_fixed_string(&self, decimals: u8) -> String {
         format!("{:.*}", decimals as usize, self)
     }
+    fn round_to_signed_string(&self, decimals: u8) -> String {
+        format!("{:+.*}", decimals as usize, self)
+    }
 }
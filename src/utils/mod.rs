@@ -1,3 +1,4 @@
+pub mod chart;
 pub mod float;
 pub mod retry;
 pub mod spinner;
@@ -13,7 +13,9 @@ pub fn get_retry_time(response: &Response) -> Option<f32> {
         .and_then(|x| x.parse().ok())
 }
 
-pub async fn handle_retry<F, Fut, T>(mut task: F) -> T
+/// Retries the task, moving to the next RPC after every few attempts, until it succeeds or
+/// the maximum number of attempts is reached
+async fn retry<F, Fut, T>(mut task: F, max_attempts: Option<usize>) -> Option<T>
 where
     F: FnMut(usize) -> Fut,
     Fut: Future<Output = (Option<T>, Option<f32>)>,
@@ -22,10 +24,13 @@ where
     let mut rpc_index = 0;
     let maximum_retry_time_secs = 1.0;
     loop {
+        if max_attempts.is_some_and(|x| retries >= x) {
+            return None;
+        }
         let (result, retry_time) = task(rpc_index).await;
         match result {
             Some(x) => {
-                return x;
+                return Some(x);
             }
             None => {
                 if retries >= 2 {
@@ -43,6 +48,14 @@ where
     }
 }
 
+pub async fn handle_retry<F, Fut, T>(task: F) -> T
+where
+    F: FnMut(usize) -> Fut,
+    Fut: Future<Output = (Option<T>, Option<f32>)>,
+{
+    retry(task, None).await.unwrap()
+}
+
 pub async fn handle_retry_indexed<F, Fut, T>(index: usize, task: F) -> (usize, T)
 where
     F: FnMut(usize) -> Fut,
@@ -50,3 +63,17 @@ where
 {
     (index, handle_retry(task).await)
 }
+
+/// Same as `handle_retry_indexed`, but gives up after the given number of attempts, for
+/// tasks making many calls that could otherwise keep failing forever
+pub async fn handle_limited_retry_indexed<F, Fut, T>(
+    index: usize,
+    max_attempts: usize,
+    task: F,
+) -> (usize, Option<T>)
+where
+    F: FnMut(usize) -> Fut,
+    Fut: Future<Output = (Option<T>, Option<f32>)>,
+{
+    (index, retry(task, Some(max_attempts)).await)
+}
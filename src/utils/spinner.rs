@@ -15,6 +15,8 @@ static FRAMES: [&str; 8] = ["⣷", "⣯", "⣟", "⡿", "⢿", "⣻", "⣽", "
 pub struct Spinner {
     running: Arc<AtomicBool>,
     pub frame_duration: Duration,
+    /// Keeps the spinner from being drawn, e.g. when the output is meant for other programs
+    pub is_hidden: bool,
     progress: Arc<AtomicUsize>,
     total: Arc<AtomicUsize>,
     desc: Arc<Mutex<Option<String>>>,
@@ -25,6 +27,7 @@ impl Default for Spinner {
         Spinner {
             running: Arc::new(AtomicBool::new(false)),
             frame_duration: Duration::from_millis(40),
+            is_hidden: false,
             progress: Arc::new(AtomicUsize::new(0)),
             total: Arc::new(AtomicUsize::new(0)),
             desc: Arc::new(Mutex::new(None)),
@@ -37,6 +40,9 @@ impl Spinner {
         Self::default()
     }
     pub fn start(&self, extra_msg: Option<&'static str>) {
+        if self.is_hidden {
+            return;
+        }
         self.running.store(true, Ordering::Relaxed);
         let running = self.running.clone();
         let duration = self.frame_duration;
@@ -100,6 +106,9 @@ impl Spinner {
         *guard = desc;
     }
     fn cleanup(&self) {
+        if self.is_hidden {
+            return;
+        }
         print!("\r{}\r", " ".repeat(80));
         io::Write::flush(&mut io::stdout()).unwrap();
         thread::sleep(self.frame_duration);
@@ -8,6 +8,8 @@ pub struct Table {
     color_first_row: bool,
     spacing: usize,
     pub title: String,
+    /// Indexes of the rows drawn highlighted in the terminal
+    pub highlighted_rows: Vec<usize>,
 }
 
 impl From<Vec<Vec<String>>> for Table {
@@ -19,6 +21,116 @@ impl From<Vec<Vec<String>>> for Table {
     }
 }
 
+/// Renders tables in a format other than the terminal one, which is `Display`
+pub trait TableRenderer {
+    fn render(&self, table: &Table) -> String;
+    /// Renders several tables into a single document
+    fn render_document(&self, tables: &[Table]) -> String {
+        tables
+            .iter()
+            .map(|t| self.render(t))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Comma-separated values, leaving the title out. Documents hold each table as a section
+/// starting with its title, separated by blank lines
+pub struct CsvRenderer;
+
+impl CsvRenderer {
+    fn render_row(row: &[String]) -> String {
+        row.iter()
+            .map(|field| {
+                if field.contains([',', '"', '\n']) {
+                    format!("\"{}\"", field.replace('"', "\"\""))
+                } else {
+                    field.clone()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+            + "\n"
+    }
+}
+
+impl TableRenderer for CsvRenderer {
+    fn render(&self, table: &Table) -> String {
+        table.rows.iter().map(|row| Self::render_row(row)).collect()
+    }
+    fn render_document(&self, tables: &[Table]) -> String {
+        tables
+            .iter()
+            .map(|table| {
+                let title = match table.title.as_str() {
+                    "" => String::new(),
+                    title => Self::render_row(&[title.to_string()]),
+                };
+                title + &self.render(table)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// GitHub flavored Markdown, with the title as a heading
+pub struct MarkdownRenderer;
+
+impl TableRenderer for MarkdownRenderer {
+    fn render(&self, table: &Table) -> String {
+        let mut output = String::new();
+        if table.title != String::default() {
+            output += &format!("## {}\n\n", table.title);
+        }
+        for (i, row) in table.rows.iter().enumerate() {
+            let fields = row
+                .iter()
+                .map(|field| field.replace('|', "\\|"))
+                .collect::<Vec<_>>();
+            output += &format!("| {} |\n", fields.join(" | "));
+            if i == 0 {
+                output += &format!("|{}\n", " --- |".repeat(row.len()));
+            }
+        }
+        output
+    }
+}
+
+/// HTML table, with the title as a heading
+pub struct HtmlRenderer;
+
+impl HtmlRenderer {
+    fn escape(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+}
+
+impl TableRenderer for HtmlRenderer {
+    fn render(&self, table: &Table) -> String {
+        let mut output = String::new();
+        if table.title != String::default() {
+            output += &format!("<h2>{}</h2>\n", Self::escape(&table.title));
+        }
+        output += "<table>\n";
+        for (i, row) in table.rows.iter().enumerate() {
+            let tag = if i == 0 { "th" } else { "td" };
+            let cells = row
+                .iter()
+                .map(|field| format!("<{tag}>{}</{tag}>", Self::escape(field)))
+                .collect::<String>();
+            output += &format!("<tr>{cells}</tr>\n");
+        }
+        output + "</table>\n"
+    }
+    fn render_document(&self, tables: &[Table]) -> String {
+        let body = tables.iter().map(|t| self.render(t)).collect::<String>();
+        format!("<!DOCTYPE html>\n<html>\n<body>\n{body}</body>\n</html>\n")
+    }
+}
+
 impl Default for Table {
     fn default() -> Self {
         Self {
@@ -26,6 +138,7 @@ impl Default for Table {
             rows: Vec::default(),
             color_first_row: true,
             spacing: 2,
+            highlighted_rows: Vec::default(),
         }
     }
 }
@@ -59,6 +172,8 @@ impl Display for Table {
                 "{}",
                 if self.color_first_row && i == 0 {
                     row.as_str().to_colored()
+                } else if self.highlighted_rows.contains(&i) {
+                    row.as_str().to_highlighted()
                 } else {
                     row
                 }
@@ -68,3 +183,46 @@ impl Display for Table {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{CsvRenderer, Table, TableRenderer};
+
+    fn get_table(rows: &[&[&str]]) -> Table {
+        Table::from(
+            rows.iter()
+                .map(|row| row.iter().map(|field| field.to_string()).collect())
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn csv_quotes_special_fields() {
+        let table = get_table(&[
+            &["Token", "Balance"],
+            &["ETH", "1.5"],
+            &["A, B", "say \"hi\""],
+        ]);
+        assert_eq!(
+            CsvRenderer.render(&table),
+            "Token,Balance\nETH,1.5\n\"A, B\",\"say \"\"hi\"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn csv_document_holds_every_table() {
+        let mut tables = [
+            get_table(&[&["Token", "Balance"], &["ETH", "1.5"]]),
+            get_table(&[
+                &["Chain", "Balance (USD)", "Share"],
+                &["Ethereum", "10", "100%"],
+            ]),
+        ];
+        tables[1].title = "By chain, in USD".to_string();
+        assert_eq!(
+            CsvRenderer.render_document(&tables),
+            "Token,Balance\nETH,1.5\n\n\"By chain, in USD\"\nChain,Balance (USD),Share\nEthereum,10,100%\n"
+        );
+        assert_eq!(CsvRenderer.render_document(&[]), "");
+    }
+}
@@ -1,6 +1,8 @@
 pub trait StylizedText {
     fn to_colored(&self) -> String;
     fn to_title(&self) -> String;
+    fn to_dimmed(&self) -> String;
+    fn to_highlighted(&self) -> String;
 }
 
 impl StylizedText for &str {
@@ -10,4 +12,10 @@ impl StylizedText for &str {
     fn to_title(&self) -> String {
         format!("{}\n{}", self, "=".repeat(self.len()))
     }
+    fn to_dimmed(&self) -> String {
+        format!("\x1b[2m{self}\x1b[0m")
+    }
+    fn to_highlighted(&self) -> String {
+        format!("\x1b[33m{self}\x1b[0m")
+    }
 }